@@ -0,0 +1,193 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::time::Duration;
+use kira::manager::{AudioManager, AudioManagerSettings};
+use kira::manager::backend::cpal::CpalBackend;
+use kira::sound::FromFileError;
+use kira::sound::static_sound::StaticSoundData;
+use kira::sound::streaming::{StreamingSoundData, StreamingSoundHandle, StreamingSoundSettings};
+use kira::track::{TrackBuilder, TrackHandle};
+use kira::tween::Tween;
+use crate::config::Config;
+
+// File extensions songs can be in, all decoded the same way whatever the platform
+pub const SUPPORTED_AUDIO_FORMATS: [&str; 4] = ["ogg", "mp3", "flac", "wav"];
+
+/// A short sound played over the music, on its own track so it has its own volume
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Sfx {
+    HitPerfect,
+    HitGood,
+    HitOk,
+    Miss,
+    // Starts as a laser begins warming up
+    LaserCharge,
+    LaserFire,
+    UIHover,
+    UIClick
+}
+
+impl Sfx {
+    pub const ALL: [Sfx; 8] = [
+        Sfx::HitPerfect, Sfx::HitGood, Sfx::HitOk, Sfx::Miss,
+        Sfx::LaserCharge, Sfx::LaserFire, Sfx::UIHover, Sfx::UIClick
+    ];
+
+    pub fn path(&self) -> &'static str {
+        match self {
+            Sfx::HitPerfect => "assets/sounds/hit_perfect.wav",
+            Sfx::HitGood => "assets/sounds/hit_good.wav",
+            Sfx::HitOk => "assets/sounds/hit_ok.wav",
+            Sfx::Miss => "assets/sounds/miss.wav",
+            Sfx::LaserCharge => "assets/sounds/laser_charge.wav",
+            Sfx::LaserFire => "assets/sounds/laser_fire.wav",
+            Sfx::UIHover => "assets/sounds/ui_hover.wav",
+            Sfx::UIClick => "assets/sounds/ui_click.wav"
+        }
+    }
+}
+
+/// Why a music file couldn't be played, worded to be shown on an error scene
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AudioError {
+    Missing(String),
+    Unsupported(String),
+    Undecodable(String),
+    Playback(String)
+}
+
+impl Display for AudioError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioError::Missing(file) => write!(f, "Music file {} is missing", file),
+            AudioError::Unsupported(file) => write!(f, "{} isn't ogg, mp3, flac or wav", file),
+            AudioError::Undecodable(file) => write!(f, "Music file {} can't be decoded", file),
+            AudioError::Playback(file) => write!(f, "Music file {} couldn't be played", file)
+        }
+    }
+}
+
+/// Checks `path` is in a format the game can decode, without opening it
+pub fn check_format(path: &str) -> Result<(), AudioError> {
+    let extension = Path::new(path).extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_lowercase());
+
+    match extension {
+        Some(extension) if SUPPORTED_AUDIO_FORMATS.contains(&extension.as_str()) => Ok(()),
+        _ => Err(AudioError::Unsupported(file_name(path)))
+    }
+}
+
+/// Just the file name, since full paths don't fit on the error scene
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name()
+        .and_then(|x| x.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// The one audio manager, shared so scenes don't each open the audio device.
+/// Music and sound effects go through their own sub-tracks, which the volume settings are applied to.
+pub struct Audio {
+    manager: AudioManager<CpalBackend>,
+    music_track: TrackHandle,
+    sfx_track: TrackHandle
+}
+
+impl Audio {
+    pub fn new(config: &Config) -> Self {
+        let mut manager = AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
+        let music_track = manager.add_sub_track(TrackBuilder::new().volume(config.music_volume)).unwrap();
+        let sfx_track = manager.add_sub_track(TrackBuilder::new().volume(config.sfx_volume)).unwrap();
+
+        Self {
+            manager,
+            music_track,
+            sfx_track
+        }
+    }
+
+    /// Call after the volumes in `config` change
+    pub fn set_volumes(&mut self, config: &Config) {
+        let _ = self.music_track.set_volume(config.music_volume, Default::default());
+        let _ = self.sfx_track.set_volume(config.sfx_volume, Default::default());
+    }
+
+    /// Plays a sound effect through to the end, a sound that can't be played is skipped
+    pub fn play_sfx(&mut self, sound: StaticSoundData) {
+        let sound = sound.with_modified_settings(|x| x.output_destination(&self.sfx_track));
+        let _ = self.manager.play(sound);
+    }
+
+    /// Streams the music at `path` from disk as it plays, so a song doesn't have to be decoded before it starts.
+    /// It keeps playing until the returned Music is dropped.
+    pub fn play_music(&mut self, path: &str) -> Result<Music, AudioError> {
+        self.play_music_with(path, StreamingSoundSettings::default())
+    }
+
+    /// Same as play_music, for music that starts part way through, loops or fades in
+    pub fn play_music_with(&mut self, path: &str, settings: StreamingSoundSettings) -> Result<Music, AudioError> {
+        check_format(path)?;
+
+        let settings = settings.output_destination(&self.music_track);
+        let sound = StreamingSoundData::from_file(path, settings).map_err(|err| match err {
+            FromFileError::IoError(_) => AudioError::Missing(file_name(path)),
+            _ => AudioError::Undecodable(file_name(path))
+        })?;
+
+        match self.manager.play(sound) {
+            Ok(handle) => Ok(Music {
+                handle,
+                stop_on_drop: true
+            }),
+            Err(_) => Err(AudioError::Playback(file_name(path)))
+        }
+    }
+}
+
+/// A playing track that stops when it's dropped, so leaving a scene or replacing its music silences the old one
+pub struct Music {
+    handle: StreamingSoundHandle<FromFileError>,
+    // False once it's fading out, so dropping it doesn't cut the fade short
+    stop_on_drop: bool
+}
+
+impl Music {
+    /// Fades the music out over `seconds` and lets it stop on its own
+    pub fn fade_out(mut self, seconds: f32) {
+        let _ = self.handle.stop(fade_tween(seconds));
+        self.stop_on_drop = false;
+    }
+}
+
+/// A tween lasting `seconds`, for fading music in and out
+pub fn fade_tween(seconds: f32) -> Tween {
+    Tween {
+        duration: Duration::from_secs_f32(seconds),
+        ..Default::default()
+    }
+}
+
+impl Deref for Music {
+    type Target = StreamingSoundHandle<FromFileError>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl DerefMut for Music {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.handle
+    }
+}
+
+impl Drop for Music {
+    fn drop(&mut self) {
+        if self.stop_on_drop {
+            let _ = self.handle.stop(Default::default());
+        }
+    }
+}
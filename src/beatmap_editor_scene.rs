@@ -1,38 +1,68 @@
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 use async_trait::async_trait;
 use egui_macroquad::egui;
-use kira::manager::{AudioManager, AudioManagerSettings};
-use kira::manager::backend::cpal::CpalBackend;
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use egui_macroquad::egui::plot::{Line, Plot, PlotPoints, VLine};
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::*;
-use crate::error_scene::ErrorScene;
-
-use crate::main_menu_scene::MainMenuScene;
-use crate::note_gameplay_scene::constants::{ARROW_OFFSET, BEATS_TO_NOTE_HIT, DOWN_ARROW_POS, LEFT_ARROW_POS, NOTE_SIZE, NOTE_START_POS, RIGHT_ARROW_POS, UP_ARROW_POS};
-use crate::note_gameplay_scene::{draw_hold, draw_note, NoteGameplayScene, ReturnTo};
-use crate::note_gameplay_scene::song::Song;
+use thousands::Separable;
+use crate::game_context::GameContext;
+
+use crate::beatmap_editor_scene::chart_stats::ChartStats;
+use crate::beatmap_editor_scene::difficulty_derivation::{derive_chart, easier_difficulties, DerivationSettings, DIFFICULTY_ORDER};
+use crate::beatmap_editor_scene::inspector::{apply_attack_edit, apply_note_edit, attack_fields, inspector, validate_special_attack, InspectorKind};
+use crate::beatmap_editor_scene::laser_patterns::{find_impossible_sections, generate_pattern, ImpossibleSection, LaserPattern, PatternSettings};
+use crate::beatmap_editor_scene::recording::{Recorder, SNAP_OPTIONS};
+use crate::main_menu_scene::SongDatabase;
+use crate::note_gameplay_scene::attacks::{Attack, AttackKind};
+use crate::note_gameplay_scene::constants::{ARROW_OFFSET, ATTACK_WARMUP_BEATS, DOWN_ARROW_POS, LEFT_ARROW_POS, NOTE_SIZE, RIGHT_ARROW_POS, UP_ARROW_POS};
+use crate::note_gameplay_scene::{draw_hold, draw_note, scroll_pixels_per_beat, NoteGameplayScene};
+use crate::note_gameplay_scene::health::{HealthPreset, HealthRules};
+use crate::note_gameplay_scene::practice::PracticeLoop;
+use crate::note_gameplay_scene::song::{lane_name, lane_position, Song, LANES_TOP_TO_BOTTOM};
+use crate::note_gameplay_scene::special_notes::{draw_special_note, SpecialNote};
 use crate::porpus_scene::PorpusScene;
-use crate::scene::Scene;
+use crate::scene::{Scene, SceneChange};
+use crate::transition::Transition;
 use crate::ui::draw_text_justified;
-use crate::utils::{is_hovering_rect, quick_load_texture};
+use crate::utils::is_hovering_rect;
+
+pub mod chart_stats;
+pub mod difficulty_derivation;
+pub mod inspector;
+pub mod laser_patterns;
+pub mod recording;
 
 pub struct UndoEdit {
     pub notes: Vec<(f32, f32, f32)>,
     pub attacks: Vec<(f32, f32, f32)>,
-    pub selected_note: usize,
-    pub selected_attack: usize
+    pub special_notes: Vec<SpecialNote>,
+    pub special_attacks: Vec<Attack>,
+    pub selected_notes: Vec<usize>,
+    pub selected_attacks: Vec<usize>
+}
+
+impl UndoEdit {
+    /// The chart and selection as they are now, to go back to if the next edit is undone
+    pub fn snapshot(song: &Song, selected_notes: &[usize], selected_attacks: &[usize]) -> Self {
+        Self {
+            notes: song.notes.clone(),
+            attacks: song.attacks.clone(),
+            special_notes: song.special_notes.clone(),
+            special_attacks: song.special_attacks.clone(),
+            selected_notes: selected_notes.to_vec(),
+            selected_attacks: selected_attacks.to_vec()
+        }
+    }
 }
 
 pub struct BeatmapEditorScene {
-    pub window_context: WindowContext,
     pub song_path: String
 }
 
 #[async_trait]
 impl Scene for BeatmapEditorScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
 
         let mut last_functional_song_path = "assets/songs/easy/goldn.json".to_string();
         let mut song_path = "assets/songs/easy/goldn.json".to_string();
@@ -43,76 +73,93 @@ impl Scene for BeatmapEditorScene {
 
         let song_json = match load_string(&song_path).await {
             Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Default Song in editor is missing, try reinstalling", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Default Song in editor is missing, try reinstalling")
         };
         let mut song = match serde_json::from_str::<Song>(song_json.as_str()) {
             Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Default song in editor has been changed and is incorrect, try reinstalling", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Default song in editor has been changed and is incorrect, try reinstalling")
         };
 
+        let config = ctx.config.clone();
+
         let mut beats_per_second = song.bpm / 60.0;
-        let mut pixels_per_beat = (NOTE_START_POS - ARROW_OFFSET) / BEATS_TO_NOTE_HIT;
-
-        let mut sound_manager = AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-        let mut sound = match StaticSoundData::from_file(
-            song.song_filepath.clone(),
-            StaticSoundSettings::default(),
-        ) {
-            Ok(sound) => sound,
-            Err(_) => return Some(Box::new(ErrorScene::new("Song in editor is missing sound filepath", self.window_context.clone())))
-        };
+        let mut pixels_per_beat = scroll_pixels_per_beat(&config, song.bpm);
 
-        let mut music = sound_manager.play(sound).unwrap();
+        let mut music = match ctx.audio.play_music(&song.song_filepath) {
+            Ok(music) => music,
+            Err(err) => return SceneChange::error(&err.to_string())
+        };
         let mut reload = false;
         let mut test = false;
+        let mut practice: Option<PracticeLoop> = None;
         let mut watch = false;
 
-        let mut selected_note: usize = 10_000_000;
-        let mut selected_attack: usize = 10_000_000;
+        let mut selected_notes: Vec<usize> = vec![];
+        let mut selected_attacks: Vec<usize> = vec![];
+        let mut selected_special: Option<usize> = None;
+        // Index into special_note_templates of the kind the New button adds
+        let mut new_special_kind = 0;
+        let mut selected_special_attack: Option<usize> = None;
+        // Index into special_attack_templates of the kind the New button adds
+        let mut new_attack_kind = 0;
+        let mut inspector_editing = false;
 
         let mut pixels_per_point = 1.0;
 
         // Input Notes
-        let input_note_up = match quick_load_texture("assets/images/arrow_up.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_down = match quick_load_texture("assets/images/arrow_down.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_left = match quick_load_texture("assets/images/arrow_left.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_right = match quick_load_texture("assets/images/arrow_right.png").await {
-                Ok(tex) => tex,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            };
-        let hold_note = match quick_load_texture("assets/images/hold.png").await {
-                Ok(tex) => tex,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            };
-        let laser = match quick_load_texture("assets/images/laser.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let input_note_up = ctx.assets.arrow_up;
+        let input_note_down = ctx.assets.arrow_down;
+        let input_note_left = ctx.assets.arrow_left;
+        let input_note_right = ctx.assets.arrow_right;
+        let hold_note = ctx.assets.hold;
+        let laser = ctx.assets.laser;
 
         let mut undo_edits: Vec<UndoEdit> = vec![];
 
+        let mut recorder = Recorder::new(&config.controls);
+
+        let mut derive = false;
+        let mut overwrite_derived = false;
+        let mut derive_messages: Vec<String> = vec![];
+        // Difficulty, chart path
+        let mut derived_charts: Vec<(String, String)> = vec![];
+
+        let mut pattern_settings = PatternSettings::default();
+        let mut impossible_sections: Vec<ImpossibleSection> = vec![];
+        let mut checked_attacks: Option<Vec<Attack>> = None;
+        // The chart the statistics were last worked out for
+        let mut stats_song = song.clone();
+        let mut chart_stats = ChartStats::new(&song);
+
         let mut paused = false;
 
         let mut fps_display = false;
-        let font = load_ttf_font("assets/fonts/pixel.ttf").await.unwrap();
+        let font = ctx.assets.font;
 
         loop {
+            ctx.input.update();
+
             clear_background(BLACK);
-            set_camera(&self.window_context.camera);
+            set_camera(&ctx.window_context.camera);
             clear_background(BLACK);
 
             let beat = beats_per_second * ((music.position() * 1_000_000.0).round() / 1_000_000.0) as f32;
 
             let mut ignore_inputs = false;
+            let mut still_editing = false;
+
+            // Only re-run the feasibility check when the attacks actually changed
+            let all_attacks = song.all_attacks();
+            if checked_attacks.as_ref() != Some(&all_attacks) {
+                impossible_sections = find_impossible_sections(&all_attacks, song.bpm);
+                checked_attacks = Some(all_attacks);
+            }
+
+            // Statistics are only worked out again once the chart changes
+            if stats_song != song {
+                chart_stats = ChartStats::new(&song);
+                stats_song = song.clone();
+            }
 
             if is_key_pressed(KeyCode::F3) {
                 fps_display = !fps_display;
@@ -121,7 +168,7 @@ impl Scene for BeatmapEditorScene {
             if fps_display {
                 draw_text_justified(
                     format!("{}", get_fps()).as_str(),
-                    vec2(self.window_context.active_screen_size.x - 5.0, 5.0),
+                    vec2(ctx.window_context.active_screen_size.x - 5.0, 5.0),
                     TextParams {
                         font,
                         font_size: 40,
@@ -147,17 +194,51 @@ impl Scene for BeatmapEditorScene {
                             ui.label("J/L Move Selected Note");
                             ui.label("Y/H Increase/Decrease Hold Length");
                             ui.label("O Rotate Note");
+                            ui.label("Shift + Click Select Several Notes/Attacks");
                             ui.label("I/K Move Song Position");
-                            ui.label("+/- Increase/Decrease Menu Scale")
+                            ui.label("+/- Increase/Decrease Menu Scale");
+                            ui.label("Space Stop Recording")
                         });
 
                         let response = ui.text_edit_singleline(&mut song_path);
 
-                        if response.has_focus() {
-                            ignore_inputs = true;
-                        } else {
-                            ignore_inputs = false;
-                        }
+                        egui::ComboBox::from_label("Health Rules")
+                            .selected_text(match song.health_preset {
+                                Some(preset) => preset.name(),
+                                None => "By Difficulty"
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut song.health_preset, None, "By Difficulty");
+                                for preset in [HealthPreset::Lenient, HealthPreset::Normal, HealthPreset::Hard] {
+                                    ui.selectable_value(&mut song.health_preset, Some(preset), preset.name());
+                                }
+                            });
+                        ui.collapsing("Health Overrides", |ui| {
+                            let preset_rules = HealthRules::from_preset(HealthPreset::for_song(&song, &song_path));
+                            let overrides = &mut song.health_overrides;
+                            for (value, label, preset_value) in [
+                                (&mut overrides.max_health, "Max Health", preset_rules.max_health),
+                                (&mut overrides.hit_gain, "Hit Gain", preset_rules.hit_gain),
+                                (&mut overrides.miss_loss, "Miss Loss", preset_rules.miss_loss),
+                                (&mut overrides.incorrect_loss, "Incorrect Loss", preset_rules.incorrect_loss),
+                                (&mut overrides.mine_loss, "Mine Loss", preset_rules.mine_loss),
+                                (&mut overrides.laser_loss, "Laser Loss", preset_rules.laser_loss)
+                            ] {
+                                ui.horizontal(|ui| {
+                                    let mut overridden = value.is_some();
+                                    if ui.checkbox(&mut overridden, label).changed() {
+                                        *value = overridden.then_some(preset_value);
+                                    }
+                                    if let Some(value) = value {
+                                        ui.add(egui::DragValue::new(value).clamp_range(0..=10000));
+                                    } else {
+                                        ui.label(preset_value.to_string());
+                                    }
+                                });
+                            }
+                        });
+
+                        ignore_inputs = response.has_focus() || recorder.recording;
 
                         if ui.button("Save").clicked() {
                             let mut file = File::create(song_path.clone()).unwrap();
@@ -198,109 +279,242 @@ impl Scene for BeatmapEditorScene {
                             reload = true;
                             watch = true;
                         }
+
+                        if !selected_notes.is_empty() && ui.button("Practice Selection").clicked() {
+                            let mut file = File::create(song_path.clone()).unwrap();
+                            let cloned_song = song.clone();
+                            file.write_all(serde_json::to_string_pretty(&cloned_song).unwrap().as_ref()).unwrap();
+
+                            let start_beat = selected_notes.iter().map(|x| song.notes[*x].0).fold(f32::MAX, f32::min);
+                            let end_beat = selected_notes.iter().map(|x| song.notes[*x].0 + song.notes[*x].2).fold(0.0, f32::max);
+
+                            reload = true;
+                            practice = Some(PracticeLoop::new(start_beat, end_beat + 1.0));
+                        }
                     });
                 egui::Window::new("Note Editor")
                     .resizable(true)
                     .show(egui_ctx, |ui| {
-                        if selected_note != 10_000_000 {
+                        if !selected_notes.is_empty() {
                             ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                                ui.add(egui::DragValue::new(&mut song.notes[selected_note].0).speed(0.0));
-                                ui.add(egui::DragValue::new(&mut song.notes[selected_note].2).speed(0.0));
+                                // Notes are already (Beat, Lane, Length)
+                                let response = inspector(ui, InspectorKind::Note, &song.notes, &selected_notes, beats_per_second, song.song_length);
+                                if let Some(edit) = response.edit {
+                                    if !inspector_editing {
+                                        undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+                                    }
+                                    for idx in &selected_notes {
+                                        apply_note_edit(&mut song.notes[*idx], edit);
+                                    }
+                                }
+                                still_editing |= response.editing;
+                                ignore_inputs |= response.editing;
 
                                 if ui.button("Delete (.)").clicked() || (is_key_pressed(KeyCode::Period) && !ignore_inputs) {
-                                    undo_edits.push(UndoEdit {
-                                        notes: song.notes.clone(),
-                                        attacks: song.attacks.clone(),
-                                        selected_note,
-                                        selected_attack
-                                    });
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                                    song.notes.remove(selected_note);
-                                    selected_note = 10_000_000;
+                                    selected_notes.sort_unstable();
+                                    for idx in selected_notes.iter().rev() {
+                                        song.notes.remove(*idx);
+                                    }
+                                    selected_notes.clear();
                                 }
                                 if ui.button("Duplicate (U)").clicked() || (is_key_pressed(KeyCode::U) && !ignore_inputs) {
-                                    undo_edits.push(UndoEdit {
-                                        notes: song.notes.clone(),
-                                        attacks: song.attacks.clone(),
-                                        selected_note,
-                                        selected_attack
-                                    });
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                                    let note = song.notes[selected_note];
-                                    song.notes.push((note.0 + 0.125, note.1, note.2));
-                                    selected_note = song.notes.len() - 1;
+                                    let mut duplicates = vec![];
+                                    for idx in &selected_notes {
+                                        let note = song.notes[*idx];
+                                        song.notes.push((note.0 + 0.125, note.1, note.2));
+                                        duplicates.push(song.notes.len() - 1);
+                                    }
+                                    selected_notes = duplicates;
                                 }
                                 if ui.button("Rotate (O)").clicked() || (is_key_pressed(KeyCode::O) && !ignore_inputs) {
-                                    undo_edits.push(UndoEdit {
-                                        notes: song.notes.clone(),
-                                        attacks: song.attacks.clone(),
-                                        selected_note,
-                                        selected_attack
-                                    });
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                                    song.notes[selected_note].1 += 1.0;
-                                    if song.notes[selected_note].1 > 4.0 {
-                                        song.notes[selected_note].1 = 1.0;
+                                    for idx in &selected_notes {
+                                        song.notes[*idx].1 += 1.0;
+                                        if song.notes[*idx].1 > 4.0 {
+                                            song.notes[*idx].1 = 1.0;
+                                        }
                                     }
                                 }
+                                if ui.button("Deselect").clicked() {
+                                    selected_notes.clear();
+                                }
                             });
                         }
 
                         if ui.button("New (N)").clicked() || (is_key_pressed(KeyCode::N) && !ignore_inputs) {
-                            undo_edits.push(UndoEdit {
-                                notes: song.notes.clone(),
-                                attacks: song.attacks.clone(),
-                                selected_note,
-                                selected_attack
-                            });
+                            undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
                             song.notes.push(((beat + 1.5).floor(), 1.0, 0.0));
-                            selected_note = song.notes.len() - 1;
+                            selected_notes = vec![song.notes.len() - 1];
+                        }
+                    });
+                egui::Window::new("Statistics")
+                    .resizable(true)
+                    .show(egui_ctx, |ui| {
+                        let stats = &chart_stats;
+
+                        egui::Grid::new("chart_stats").num_columns(2).show(ui, |ui| {
+                            for (lane, count) in LANES_TOP_TO_BOTTOM.iter().zip(stats.lane_counts) {
+                                ui.label(format!("{} Notes", lane_name(*lane)));
+                                ui.label(count.separate_with_commas());
+                                ui.end_row();
+                            }
+
+                            ui.label("Holds");
+                            ui.label(format!("{} ({:.2} beats)", stats.hold_count, stats.total_hold_beats));
+                            ui.end_row();
+
+                            ui.label("Attacks");
+                            ui.label(stats.attack_count.separate_with_commas());
+                            ui.end_row();
+
+                            ui.label("Special Notes");
+                            ui.label(stats.special_note_count.separate_with_commas());
+                            ui.end_row();
+
+                            ui.label("Notes Per Second");
+                            ui.label(format!("{:.2} avg, {:.2} peak", stats.average_nps, stats.peak_nps));
+                            ui.end_row();
+
+                            ui.label("Max Combo");
+                            ui.label(stats.max_combo.separate_with_commas());
+                            ui.end_row();
+
+                            ui.label("Max Score");
+                            ui.label(stats.max_score.separate_with_commas());
+                            ui.end_row();
+
+                            ui.label("Difficulty");
+                            ui.label(format!("{} ({:.2})", stats.difficulty_name(), stats.rating));
+                            ui.end_row();
+                        });
+
+                        Plot::new("nps_plot")
+                            .height(120.0)
+                            .allow_drag(false)
+                            .allow_zoom(false)
+                            .include_y(0.0)
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(Line::new(PlotPoints::from(stats.nps_over_time.clone())).name("Notes Per Second"));
+                                plot_ui.vline(VLine::new((beat / beats_per_second) as f64));
+                            });
+                    });
+                egui::Window::new("Difficulties")
+                    .resizable(true)
+                    .show(egui_ctx, |ui| {
+                        let master_difficulty = Path::new(&song_path).parent()
+                            .and_then(|x| x.file_name())
+                            .and_then(|x| x.to_str())
+                            .unwrap_or_default();
+
+                        if easier_difficulties(master_difficulty).len() == DIFFICULTY_ORDER.len() {
+                            ui.label("Save the chart in a difficulty folder to derive easier charts from it");
+                        } else {
+                            ui.label(format!("Master chart: {master_difficulty}"));
+                            ui.checkbox(&mut overwrite_derived, "Overwrite existing charts");
+                            if ui.button("Derive Easier Difficulties").clicked() {
+                                derive = true;
+                            }
+                        }
+
+                        for message in &derive_messages {
+                            ui.label(message);
+                        }
+                        for (difficulty, path) in &derived_charts {
+                            if ui.button(format!("Open {difficulty}")).clicked() {
+                                song_path = path.clone();
+                                reload = true;
+                            }
+                        }
+                    });
+                egui::Window::new("Recording")
+                    .resizable(true)
+                    .show(egui_ctx, |ui| {
+                        egui::ComboBox::from_label("Snap")
+                            .selected_text(recorder.snap_name())
+                            .show_ui(ui, |ui| {
+                                for (snap, name) in SNAP_OPTIONS {
+                                    ui.selectable_value(&mut recorder.snap, snap, name);
+                                }
+                            });
+
+                        if recorder.recording {
+                            ui.colored_label(egui::Color32::RED, "Recording, tap the lane keys");
+                            if ui.button("Stop").clicked() {
+                                recorder.stop(beat);
+                            }
+                        } else if ui.button("Record").clicked() {
+                            recorder.start();
+                            music.resume(Default::default()).unwrap();
+                            paused = false;
+                        }
+
+                        ui.label(format!("{} recorded notes", recorder.layer.len()));
+
+                        if !recorder.recording && !recorder.layer.is_empty() {
+                            ui.horizontal(|ui| {
+                                if ui.button("Merge").clicked() {
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                                    selected_notes = recorder.merge_into(&mut song.notes);
+                                }
+                                if ui.button("Discard").clicked() {
+                                    recorder.discard();
+                                }
+                            });
                         }
                     });
                 egui::Window::new("Attack Editor")
                     .resizable(true)
                     .show(egui_ctx, |ui| {
-                        if selected_attack != 10_000_000 {
+                        if !selected_attacks.is_empty() {
                             ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                                ui.add(egui::DragValue::new(&mut song.attacks[selected_attack].0).speed(0.0));
-                                ui.add(egui::DragValue::new(&mut song.attacks[selected_attack].1).speed(0.0));
+                                let fields: Vec<(f32, f32, f32)> = song.attacks.iter().map(attack_fields).collect();
+                                let response = inspector(ui, InspectorKind::Attack, &fields, &selected_attacks, beats_per_second, song.song_length);
+                                if let Some(edit) = response.edit {
+                                    if !inspector_editing {
+                                        undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+                                    }
+                                    for idx in &selected_attacks {
+                                        apply_attack_edit(&mut song.attacks[*idx], edit);
+                                    }
+                                }
+                                still_editing |= response.editing;
+                                ignore_inputs |= response.editing;
 
                                 if ui.button("Rotate (Q)").clicked() || (is_key_pressed(KeyCode::Q) && !ignore_inputs) {
-                                    undo_edits.push(UndoEdit {
-                                        notes: song.notes.clone(),
-                                        attacks: song.attacks.clone(),
-                                        selected_note,
-                                        selected_attack
-                                    });
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                                    song.attacks[selected_attack].2 += 1.0;
-                                    if song.attacks[selected_attack].2 > 4.0 {
-                                        song.attacks[selected_attack].2 = 1.0;
+                                    for idx in &selected_attacks {
+                                        song.attacks[*idx].2 += 1.0;
+                                        if song.attacks[*idx].2 > 4.0 {
+                                            song.attacks[*idx].2 = 1.0;
+                                        }
                                     }
                                 }
 
                                 if ui.button("Delete (X)").clicked() || (is_key_pressed(KeyCode::X) && !ignore_inputs) {
-                                    undo_edits.push(UndoEdit {
-                                        notes: song.notes.clone(),
-                                        attacks: song.attacks.clone(),
-                                        selected_note,
-                                        selected_attack
-                                    });
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                                    song.attacks.remove(selected_attack);
-                                    selected_attack = 10_000_000
+                                    selected_attacks.sort_unstable();
+                                    for idx in selected_attacks.iter().rev() {
+                                        song.attacks.remove(*idx);
+                                    }
+                                    selected_attacks.clear();
+                                }
+                                if ui.button("Deselect").clicked() {
+                                    selected_attacks.clear();
                                 }
                             });
                         }
 
                         if ui.button("New (V)").clicked() || (is_key_pressed(KeyCode::V) && !ignore_inputs) {
-                            undo_edits.push(UndoEdit {
-                                notes: song.notes.clone(),
-                                attacks: song.attacks.clone(),
-                                selected_note,
-                                selected_attack
-                            });
+                            undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
                             let spawn_loc= if beat - beat.floor() <= 0.75 && beat - beat.floor() >= 0.25 {
                                 beat.floor() + 0.5
@@ -309,38 +523,315 @@ impl Scene for BeatmapEditorScene {
                             };
 
                             song.attacks.push((spawn_loc, 4.0, 1.0));
-                            selected_attack = song.attacks.len() - 1;
+                            selected_attacks = vec![song.attacks.len() - 1];
+                        }
+
+                        ui.separator();
+                        ui.collapsing("Patterns", |ui| {
+                            egui::ComboBox::from_label("Pattern")
+                                .selected_text(pattern_settings.pattern.name())
+                                .show_ui(ui, |ui| {
+                                    for pattern in [LaserPattern::Sweep, LaserPattern::Alternate, LaserPattern::DoubleLane] {
+                                        ui.selectable_value(&mut pattern_settings.pattern, pattern, pattern.name());
+                                    }
+                                });
+                            lane_combo_box(ui, "First Lane", &mut pattern_settings.first_lane);
+                            if pattern_settings.pattern != LaserPattern::Sweep {
+                                lane_combo_box(ui, "Second Lane", &mut pattern_settings.second_lane);
+                            }
+                            ui.add(egui::DragValue::new(&mut pattern_settings.count).clamp_range(1..=64).prefix("Count: "));
+                            ui.add(egui::DragValue::new(&mut pattern_settings.spacing).speed(0.125).clamp_range(0.125..=16.0).prefix("Spacing: "));
+                            ui.add(egui::DragValue::new(&mut pattern_settings.length).speed(0.25).clamp_range(0.25..=16.0).prefix("Length: "));
+
+                            if ui.button("Insert Pattern").clicked() {
+                                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                                let spawn_loc = if beat - beat.floor() <= 0.75 && beat - beat.floor() >= 0.25 {
+                                    beat.floor() + 0.5
+                                } else {
+                                    beat.round()
+                                };
+
+                                let first_new = song.attacks.len();
+                                song.attacks.extend(generate_pattern(&pattern_settings, spawn_loc));
+                                selected_attacks = (first_new..song.attacks.len()).collect();
+                            }
+                        });
+
+                        ui.separator();
+                        if impossible_sections.is_empty() {
+                            ui.label("All laser sections are passable");
+                        } else {
+                            ui.colored_label(egui::Color32::RED, format!("{} impossible laser section(s)", impossible_sections.len()));
+                            for section in &impossible_sections {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("Beats {:.2} - {:.2}", section.start_beat, section.end_beat));
+                                    if ui.button("Go").clicked() {
+                                        music.seek_to(((section.start_beat - 1.0).max(0.0) / beats_per_second) as f64).unwrap();
+                                    }
+                                });
+                            }
+                        }
+                    });
+                egui::Window::new("Special Attack Editor")
+                    .resizable(true)
+                    .show(egui_ctx, |ui| {
+                        if let Some(idx) = selected_special_attack {
+                            let mut edited = song.special_attacks[idx];
+                            let mut editing = false;
+
+                            ui.label(edited.kind.name());
+                            for (value, prefix, min) in [(&mut edited.beat, "Beat: ", 0.0), (&mut edited.length, "Length: ", 0.25), (&mut edited.warmup, "Warm-up: ", 0.0)] {
+                                let response = ui.add(egui::DragValue::new(value).speed(0.125).clamp_range(min..=song.song_length * beats_per_second).prefix(prefix));
+                                editing |= response.dragged() || response.has_focus();
+                            }
+
+                            match &mut edited.kind {
+                                AttackKind::Laser { lane } => lane_combo_box(ui, "Lane", lane),
+                                AttackKind::MovingLaser { from_lane, to_lane } => {
+                                    lane_combo_box(ui, "From Lane", from_lane);
+                                    lane_combo_box(ui, "To Lane", to_lane);
+                                }
+                                AttackKind::Projectiles { lane, count } => {
+                                    lane_combo_box(ui, "Lane", lane);
+                                    let count_response = ui.add(egui::DragValue::new(count).clamp_range(1..=32).prefix("Count: "));
+                                    editing |= count_response.dragged() || count_response.has_focus();
+                                }
+                                AttackKind::Barrage { gap_lane } => lane_combo_box(ui, "Gap Lane", gap_lane)
+                            }
+
+                            for warning in validate_special_attack(&edited) {
+                                ui.colored_label(egui::Color32::from_rgb(255, 160, 60), warning);
+                            }
+
+                            if edited != song.special_attacks[idx] {
+                                if !inspector_editing {
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+                                }
+                                song.special_attacks[idx] = edited;
+                            }
+                            still_editing |= editing;
+                            ignore_inputs |= editing;
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Delete").clicked() {
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                                    song.special_attacks.remove(idx);
+                                    selected_special_attack = None;
+                                }
+                                if ui.button("Deselect").clicked() {
+                                    selected_special_attack = None;
+                                }
+                            });
+                            ui.separator();
+                        }
+
+                        egui::ComboBox::from_label("Kind")
+                            .selected_text(special_attack_templates(0.0)[new_attack_kind].kind.name())
+                            .show_ui(ui, |ui| {
+                                for (i, template) in special_attack_templates(0.0).iter().enumerate() {
+                                    ui.selectable_value(&mut new_attack_kind, i, template.kind.name());
+                                }
+                            });
+
+                        if ui.button("New").clicked() {
+                            undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                            song.special_attacks.push(special_attack_templates(beat.round())[new_attack_kind]);
+                            selected_special_attack = Some(song.special_attacks.len() - 1);
+                        }
+                    });
+                egui::Window::new("Special Note Editor")
+                    .resizable(true)
+                    .show(egui_ctx, |ui| {
+                        if let Some(idx) = selected_special {
+                            let mut edited = song.special_notes[idx].clone();
+                            let mut editing = false;
+
+                            ui.label(edited.name());
+                            let mut note_beat = edited.beat();
+                            let beat_response = ui.add(egui::DragValue::new(&mut note_beat).speed(0.125).clamp_range(0.0..=song.song_length * beats_per_second).prefix("Beat: "));
+                            editing |= beat_response.dragged() || beat_response.has_focus();
+                            edited.set_beat(note_beat);
+
+                            match &mut edited {
+                                SpecialNote::Mine { lane, .. } | SpecialNote::ShipTarget { lane, .. } => lane_combo_box(ui, "Lane", lane),
+                                SpecialNote::Roll { lane, length, .. } => {
+                                    lane_combo_box(ui, "Lane", lane);
+                                    let length_response = ui.add(egui::DragValue::new(length).speed(0.125).clamp_range(0.25..=64.0).prefix("Length: "));
+                                    editing |= length_response.dragged() || length_response.has_focus();
+                                }
+                                SpecialNote::Chord { lanes, .. } => {
+                                    ui.horizontal(|ui| {
+                                        for lane in LANES_TOP_TO_BOTTOM {
+                                            let mut in_chord = lanes.contains(&lane);
+                                            // A chord needs at least two lanes
+                                            if ui.checkbox(&mut in_chord, lane_name(lane)).changed() {
+                                                if in_chord {
+                                                    lanes.push(lane);
+                                                } else if lanes.len() > 2 {
+                                                    lanes.retain(|x| *x != lane);
+                                                }
+                                            }
+                                        }
+                                    });
+                                }
+                            }
+
+                            if edited != song.special_notes[idx] {
+                                if !inspector_editing {
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+                                }
+                                song.special_notes[idx] = edited;
+                            }
+                            still_editing |= editing;
+                            ignore_inputs |= editing;
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Delete").clicked() {
+                                    undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                                    song.special_notes.remove(idx);
+                                    selected_special = None;
+                                }
+                                if ui.button("Deselect").clicked() {
+                                    selected_special = None;
+                                }
+                            });
+                            ui.separator();
+                        }
+
+                        egui::ComboBox::from_label("Kind")
+                            .selected_text(special_note_templates(0.0)[new_special_kind].name())
+                            .show_ui(ui, |ui| {
+                                for (i, template) in special_note_templates(0.0).iter().enumerate() {
+                                    ui.selectable_value(&mut new_special_kind, i, template.name());
+                                }
+                            });
+
+                        if ui.button("New").clicked() {
+                            undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                            song.special_notes.push(special_note_templates((beat + 1.5).floor()).swap_remove(new_special_kind));
+                            selected_special = Some(song.special_notes.len() - 1);
                         }
                     });
 
             });
 
+            inspector_editing = still_editing;
+
+            if recorder.recording {
+                ignore_inputs = true;
+
+                if is_key_pressed(KeyCode::Space) {
+                    recorder.stop(beat);
+                    music.pause(Default::default()).unwrap();
+                    paused = true;
+                } else {
+                    recorder.update(beat, &ctx.input);
+                }
+            }
+
+            if derive {
+                derive = false;
+                derive_messages.clear();
+                derived_charts.clear();
+
+                let path = Path::new(&song_path);
+                let master_difficulty = path.parent().and_then(|x| x.file_name()).and_then(|x| x.to_str()).unwrap_or_default();
+                let json_name = path.file_name().and_then(|x| x.to_str()).unwrap_or_default();
+                let songs_folder = path.parent().and_then(|x| x.parent()).unwrap_or(Path::new(""));
+
+                let master_settings = DerivationSettings::for_difficulty(master_difficulty);
+                for difficulty in easier_difficulties(master_difficulty) {
+                    let derived_path = songs_folder.join(difficulty).join(json_name);
+                    let derived_path_string = derived_path.to_string_lossy().replace('\\', "/");
+
+                    if derived_path.exists() && !overwrite_derived {
+                        derive_messages.push(format!("Kept the existing {difficulty} chart"));
+                        derived_charts.push((difficulty.to_string(), derived_path_string));
+                        continue;
+                    }
+
+                    let settings = DerivationSettings::for_difficulty(difficulty).relative_to(master_settings);
+                    let derived = derive_chart(&song, settings);
+
+                    std::fs::create_dir_all(songs_folder.join(difficulty)).unwrap();
+                    let mut file = File::create(&derived_path).unwrap();
+                    file.write_all(serde_json::to_string_pretty(&derived).unwrap().as_ref()).unwrap();
+
+                    derive_messages.push(format!("Created {difficulty} with {} notes and {} attacks", derived.notes.len(), derived.attacks.len()));
+                    derived_charts.push((difficulty.to_string(), derived_path_string));
+                }
+
+                // Make the new difficulties selectable in the song list
+                if let Ok(song_data) = load_string("assets/song_data.json").await {
+                    if let Ok(mut song_database) = serde_json::from_str::<SongDatabase>(&song_data) {
+                        match song_database.songs.iter_mut().find(|x| x.json_name == json_name) {
+                            Some(song_data) => {
+                                for (difficulty, _) in &derived_charts {
+                                    if !song_data.difficulties.contains(difficulty) {
+                                        song_data.difficulties.push(difficulty.clone());
+                                    }
+                                }
+
+                                let mut file = File::create("assets/song_data.json").unwrap();
+                                file.write_all(serde_json::to_string_pretty(&song_database).unwrap().as_ref()).unwrap();
+                            }
+                            None => derive_messages.push(format!("{json_name} isn't in song_data.json, add it there to play the new charts"))
+                        }
+                    }
+                }
+            }
+
             if reload {
                 reload = false;
 
-                if let Ok(song_json) = load_string(&song_path).await {
-                    song = serde_json::from_str::<Song>(song_json.as_str()).unwrap();
+                // Charts that can't be read, like ones with an empty chord, are refused and the last one kept
+                let loaded = load_string(&song_path).await.ok()
+                    .and_then(|x| serde_json::from_str::<Song>(x.as_str()).ok());
+                if let Some(loaded) = loaded {
+                    song = loaded;
+                    selected_notes.clear();
+                    selected_attacks.clear();
+                    recorder.stop(beat);
 
                     beats_per_second = song.bpm / 60.0;
-                    pixels_per_beat = (NOTE_START_POS - ARROW_OFFSET) / BEATS_TO_NOTE_HIT;
-
-                    sound_manager = AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-                    sound = StaticSoundData::from_file(
-                        song.song_filepath.clone(),
-                        StaticSoundSettings::default(),
-                    ).unwrap();
-                    music = sound_manager.play(sound).unwrap();
+                    pixels_per_beat = scroll_pixels_per_beat(&config, song.bpm);
+
+                    music = match ctx.audio.play_music(&song.song_filepath) {
+                        Ok(music) => music,
+                        Err(err) => return SceneChange::error(&err.to_string())
+                    };
                     last_functional_song_path = song_path.clone();
                 } else {
                     song_path = last_functional_song_path.clone();
                 }
             }
 
+            // Test plays come straight back here to the same chart instead of showing results
+            if test || practice.is_some() || watch {
+                self.song_path = song_path.clone();
+            }
             if test {
-                return Some(Box::new(NoteGameplayScene::new(self.window_context.clone(), &song_path, ReturnTo::Editor)))
+                return SceneChange::Push(Box::new(NoteGameplayScene {
+                    show_results: false,
+                    ..NoteGameplayScene::new(&song_path, Default::default())
+                }), Transition::Fade)
+            }
+            if let Some(practice_loop) = practice {
+                return SceneChange::Push(Box::new(NoteGameplayScene {
+                    show_results: false,
+                    ..NoteGameplayScene::practice(&song_path, Default::default(), practice_loop)
+                }), Transition::Fade)
             }
             if watch {
-                return Some(Box::new(PorpusScene::new(self.window_context.clone(), &song_path, ReturnTo::Editor)))
+                return SceneChange::Push(Box::new(PorpusScene {
+                    show_results: false,
+                    ..PorpusScene::new(&song_path)
+                }), Transition::Fade)
             }
 
             if is_key_pressed(KeyCode::I) && !ignore_inputs {
@@ -357,66 +848,48 @@ impl Scene for BeatmapEditorScene {
             }
 
             if music.position() >= song.song_length as f64 {
-                sound_manager = AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-                sound = StaticSoundData::from_file(
-                    song.song_filepath.clone(),
-                    StaticSoundSettings::default(),
-                ).unwrap();
-                music = sound_manager.play(sound).unwrap();
+                recorder.stop(beat);
+                music = match ctx.audio.play_music(&song.song_filepath) {
+                    Ok(music) => music,
+                    Err(err) => return SceneChange::error(&err.to_string())
+                };
             }
 
             for i in 0..song.attacks.len() {
-                let (attack_beat, last_length, note_type) = song.attacks[i];
-                let note_offset = match note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => { panic!("Error! Note type: '{note_type}' unknown") }
+                // Drawn the same way gameplay draws it, so the warm-up matches
+                let attack = Attack::from_legacy(&song.attacks[i]);
+                let laser_color = if selected_attacks.contains(&i) {
+                    WHITE
+                } else if impossible_sections.iter().any(|x| x.overlaps_attack(&attack)) {
+                    Color::new(1.0, 0.1, 0.1, 1.0)
+                } else {
+                    Color::new(1.0, 0.5, 0.6, 1.0)
                 };
+                attack.draw(beat, laser, laser_color, 1.0);
 
-                if beat >= attack_beat - 5.0 && beat <= attack_beat {
-                    let difference = 5.0 - (attack_beat - beat);
-
-                    if i == selected_attack {
-                        draw_texture_ex(laser, 0.0, note_offset - 20.0,
-                                        Color::new(1.0, 1.0, 1.0, 1.0), DrawTextureParams {
-                                dest_size: Some(vec2(difference * difference * difference * 2.0, 40.0)),
-                                ..Default::default()
-                            });
-                    } else {
-                        draw_texture_ex(laser, 0.0, note_offset - 20.0,
-                                        Color::new(1.0, 0.5, 0.6, 1.0), DrawTextureParams {
-                                dest_size: Some(vec2(difference * difference * difference * 2.0, 40.0)),
-                                ..Default::default()
-                            });
-                    }
-                }
-
-                if attack_beat >= beat || attack_beat + last_length <= beat {
-                    continue;
+                let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
+                if attack.hitboxes(beat).iter().any(|x| is_hovering_rect(*x, mouse_pos))
+                    && is_mouse_button_released(MouseButton::Left) {
+                    toggle_selection(&mut selected_attacks, i);
                 }
+            }
 
-                if i == selected_attack {
-                    draw_texture_ex(laser, 0.0, note_offset - 20.0,
-                                    Color::new(1.0, 1.0, 1.0, 1.0), DrawTextureParams {
-                            dest_size: Some(vec2(1000.0, 40.0)),
-                            ..Default::default()
-                        });
+            for i in 0..song.special_attacks.len() {
+                let attack = song.special_attacks[i];
+                let attack_color = if selected_special_attack == Some(i) {
+                    WHITE
+                } else if impossible_sections.iter().any(|x| x.overlaps_attack(&attack)) {
+                    Color::new(1.0, 0.1, 0.1, 1.0)
                 } else {
-                    draw_texture_ex(laser, 0.0, note_offset - 20.0,
-                                    Color::new(1.0, 0.5, 0.6, 1.0), DrawTextureParams {
-                            dest_size: Some(vec2(1000.0, 40.0)),
-                            ..Default::default()
-                        });
-                }
+                    Color::new(0.7, 0.5, 1.0, 1.0)
+                };
+                attack.draw(beat, laser, attack_color, 1.0);
 
-                let mouse_pos = self.window_context.camera.screen_to_world(mouse_position().into());
-                if is_hovering_rect(Rect::new(0.0, note_offset - 20.0, 708.0, 40.0), mouse_pos)
+                let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
+                if attack.hitboxes(beat).iter().any(|x| is_hovering_rect(*x, mouse_pos))
                     && is_mouse_button_released(MouseButton::Left) {
-                    selected_attack = i;
+                    selected_special_attack = Some(i);
                 }
-
             }
 
             // Draw Every Note
@@ -435,9 +908,9 @@ impl Scene for BeatmapEditorScene {
                 let hold_width = hold_length * pixels_per_beat;
                 let hold_draw_pos = note_draw_pos + hold_width;
 
-                if i == selected_note {
-                    draw_hold_white(note_type, hold_draw_pos, hold_width, hold_note, 1.0);
-                    draw_note_white(note_type, note_draw_pos, input_note_left, input_note_right, input_note_up, input_note_down);
+                if selected_notes.contains(&i) {
+                    draw_hold_tinted(note_type, hold_draw_pos, hold_width, hold_note, 1.0, WHITE);
+                    draw_note_tinted(note_type, note_draw_pos, WHITE, input_note_left, input_note_right, input_note_up, input_note_down);
                 } else {
                     draw_hold(note_type, hold_draw_pos, hold_width, hold_note, 1.0);
                     draw_note(note_type, note_draw_pos, input_note_left, input_note_right, input_note_up, input_note_down);
@@ -445,123 +918,136 @@ impl Scene for BeatmapEditorScene {
 
 
 
-                let mouse_pos = self.window_context.camera.screen_to_world(mouse_position().into());
+                let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
                 if is_hovering_rect(Rect::new(note_draw_pos, note_offset - NOTE_SIZE / 2.0, NOTE_SIZE, NOTE_SIZE), mouse_pos) && is_mouse_button_released(MouseButton::Left) {
-                    selected_note = i;
+                    toggle_selection(&mut selected_notes, i);
                 }
             }
 
-            if is_key_pressed(KeyCode::L) && selected_note != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
+            // Draw the special notes, with a box around the selected one
+            for i in 0..song.special_notes.len() {
+                let special_note = &song.special_notes[i];
+                let note_draw_pos = ((special_note.beat() - beat) * pixels_per_beat) + (ARROW_OFFSET - NOTE_SIZE / 2.0);
+                draw_special_note(special_note, note_draw_pos, pixels_per_beat, input_note_left, input_note_right, input_note_up, input_note_down);
+
+                let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
+                for lane in special_note.lanes() {
+                    let note_rect = Rect::new(note_draw_pos, lane_position(lane) - NOTE_SIZE / 2.0, NOTE_SIZE, NOTE_SIZE);
+                    if selected_special == Some(i) {
+                        draw_rectangle_lines(note_rect.x - 2.0, note_rect.y - 2.0, note_rect.w + 4.0, note_rect.h + 4.0, 2.0, WHITE);
+                    }
+                    if is_hovering_rect(note_rect, mouse_pos) && is_mouse_button_released(MouseButton::Left) {
+                        selected_special = Some(i);
+                    }
+                }
+            }
 
-                song.notes[selected_note].0 += match is_key_down(KeyCode::LeftShift) {
-                    true => 0.25,
-                    false => 0.125
-                };
+            // Draw the recorded layer faded out until it's merged
+            for (note_beat, note_type, hold_length) in &recorder.layer {
+                let note_draw_pos = ((note_beat - beat) * pixels_per_beat) + (ARROW_OFFSET - NOTE_SIZE / 2.0);
+
+                let hold_width = hold_length * pixels_per_beat;
+                let hold_draw_pos = note_draw_pos + hold_width;
+
+                let color = Color::new(1.0, 1.0, 1.0, 0.4);
+                draw_hold_tinted(*note_type, hold_draw_pos, hold_width, hold_note, 1.0, color);
+                draw_note_tinted(*note_type, note_draw_pos, color, input_note_left, input_note_right, input_note_up, input_note_down);
             }
-            if is_key_pressed(KeyCode::J) && selected_note != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
-
-                song.notes[selected_note].0 -= match is_key_down(KeyCode::LeftShift) {
-                    true => 0.25,
-                    false => 0.125
-                };
+
+            // Mark the impossible laser sections along the timeline
+            for section in &impossible_sections {
+                let start_pos = ((section.start_beat - beat) * pixels_per_beat) + ARROW_OFFSET;
+                let end_pos = ((section.end_beat - beat) * pixels_per_beat) + ARROW_OFFSET;
+                draw_rectangle(start_pos, 0.0, (end_pos - start_pos).max(2.0), 8.0, Color::new(1.0, 0.1, 0.1, 0.8));
             }
-            if is_key_pressed(KeyCode::Y) && selected_note != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
-
-                song.notes[selected_note].2 += match is_key_down(KeyCode::LeftShift) {
-                    true => 0.25,
-                    false => 0.125
-                };
 
-                song.notes[selected_note].2 = song.notes[selected_note].2.max(0.0);
+            if is_key_pressed(KeyCode::L) && !selected_notes.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                for idx in &selected_notes {
+                    song.notes[*idx].0 += match is_key_down(KeyCode::LeftShift) {
+                        true => 0.25,
+                        false => 0.125
+                    };
+                }
             }
-            if is_key_pressed(KeyCode::H) && selected_note != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
+            if is_key_pressed(KeyCode::J) && !selected_notes.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                for idx in &selected_notes {
+                    song.notes[*idx].0 -= match is_key_down(KeyCode::LeftShift) {
+                        true => 0.25,
+                        false => 0.125
+                    };
+                }
+            }
+            if is_key_pressed(KeyCode::Y) && !selected_notes.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                song.notes[selected_note].2 -= match is_key_down(KeyCode::LeftShift) {
-                    true => 0.25,
-                    false => 0.125
-                };
+                for idx in &selected_notes {
+                    song.notes[*idx].2 += match is_key_down(KeyCode::LeftShift) {
+                        true => 0.25,
+                        false => 0.125
+                    };
 
-                song.notes[selected_note].2 = song.notes[selected_note].2.max(0.0);
+                    song.notes[*idx].2 = song.notes[*idx].2.max(0.0);
+                }
             }
+            if is_key_pressed(KeyCode::H) && !selected_notes.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-            if is_key_pressed(KeyCode::A) && selected_attack != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
+                for idx in &selected_notes {
+                    song.notes[*idx].2 -= match is_key_down(KeyCode::LeftShift) {
+                        true => 0.25,
+                        false => 0.125
+                    };
 
-                song.attacks[selected_attack].0 -= match is_key_down(KeyCode::LeftShift) {
-                    true => 0.25,
-                    false => 0.125
-                };
+                    song.notes[*idx].2 = song.notes[*idx].2.max(0.0);
+                }
             }
 
-            if is_key_pressed(KeyCode::D) && selected_attack != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
+            if is_key_pressed(KeyCode::A) && !selected_attacks.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                song.attacks[selected_attack].0 += match is_key_down(KeyCode::LeftShift) {
-                    true => 0.25,
-                    false => 0.125
-                };
+                for idx in &selected_attacks {
+                    song.attacks[*idx].0 -= match is_key_down(KeyCode::LeftShift) {
+                        true => 0.25,
+                        false => 0.125
+                    };
+                }
             }
 
-            if is_key_pressed(KeyCode::R) && selected_attack != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
+            if is_key_pressed(KeyCode::D) && !selected_attacks.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                song.attacks[selected_attack].1 += match is_key_down(KeyCode::LeftShift) {
-                    true => 0.5,
-                    false => 0.25
-                };
+                for idx in &selected_attacks {
+                    song.attacks[*idx].0 += match is_key_down(KeyCode::LeftShift) {
+                        true => 0.25,
+                        false => 0.125
+                    };
+                }
             }
 
-            if is_key_pressed(KeyCode::F) && selected_attack != 10_000_000 && !ignore_inputs {
-                undo_edits.push(UndoEdit {
-                    notes: song.notes.clone(),
-                    attacks: song.attacks.clone(),
-                    selected_note,
-                    selected_attack
-                });
+            if is_key_pressed(KeyCode::R) && !selected_attacks.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
 
-                song.attacks[selected_attack].1 -= match is_key_down(KeyCode::LeftShift) {
-                    true => 0.5,
-                    false => 0.25
-                };
+                for idx in &selected_attacks {
+                    song.attacks[*idx].1 += match is_key_down(KeyCode::LeftShift) {
+                        true => 0.5,
+                        false => 0.25
+                    };
+                }
+            }
+
+            if is_key_pressed(KeyCode::F) && !selected_attacks.is_empty() && !ignore_inputs {
+                undo_edits.push(UndoEdit::snapshot(&song, &selected_notes, &selected_attacks));
+
+                for idx in &selected_attacks {
+                    song.attacks[*idx].1 -= match is_key_down(KeyCode::LeftShift) {
+                        true => 0.5,
+                        false => 0.25
+                    };
+                }
             }
 
             if is_key_pressed(KeyCode::Z) && !ignore_inputs && !undo_edits.is_empty() && is_key_down(KeyCode::LeftControl) {
@@ -569,8 +1055,12 @@ impl Scene for BeatmapEditorScene {
 
                 song.notes = undo.notes;
                 song.attacks = undo.attacks;
-                selected_note = undo.selected_note;
-                selected_attack = undo.selected_attack;
+                song.special_notes = undo.special_notes;
+                song.special_attacks = undo.special_attacks;
+                selected_special = None;
+                selected_special_attack = None;
+                selected_notes = undo.selected_notes;
+                selected_attacks = undo.selected_attacks;
             }
 
             // Draw the Input Notes
@@ -591,7 +1081,7 @@ impl Scene for BeatmapEditorScene {
                 ..Default::default()
             });
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
 
             // Menu Scaling
             if is_key_pressed(KeyCode::Equal) {
@@ -608,11 +1098,7 @@ impl Scene for BeatmapEditorScene {
 
             // Quit Condition
             if is_key_pressed(KeyCode::Escape) && is_key_down(KeyCode::LeftShift) && !ignore_inputs {
-                return Some(Box::new(MainMenuScene {
-                    window_context: self.window_context.clone(),
-                    selected_difficulty: None,
-                    selected_song_idx: None
-                }));
+                return SceneChange::Pop(Transition::SlideRight);
             }
 
             next_frame().await;
@@ -620,29 +1106,29 @@ impl Scene for BeatmapEditorScene {
     }
 }
 
-pub fn draw_note_white(direction: f32, location: f32, left_tex: Texture2D, right_tex: Texture2D, up_tex: Texture2D, down_tex: Texture2D) {
+pub fn draw_note_tinted(direction: f32, location: f32, color: Color, left_tex: Texture2D, right_tex: Texture2D, up_tex: Texture2D, down_tex: Texture2D) {
     let direction = direction.round() as i32;
     match direction {
         1 => { // Right
-            draw_texture_ex(right_tex, location, RIGHT_ARROW_POS - NOTE_SIZE / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(right_tex, location, RIGHT_ARROW_POS - NOTE_SIZE / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(NOTE_SIZE, NOTE_SIZE)),
                 ..Default::default()
             });
         },
         2 => { // Left
-            draw_texture_ex(left_tex, location, LEFT_ARROW_POS - NOTE_SIZE / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(left_tex, location, LEFT_ARROW_POS - NOTE_SIZE / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(NOTE_SIZE, NOTE_SIZE)),
                 ..Default::default()
             });
         },
         3 => { // Up
-            draw_texture_ex(up_tex, location, UP_ARROW_POS - NOTE_SIZE / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(up_tex, location, UP_ARROW_POS - NOTE_SIZE / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(NOTE_SIZE, NOTE_SIZE)),
                 ..Default::default()
             });
         },
         4 => { // Down
-            draw_texture_ex(down_tex, location, DOWN_ARROW_POS - NOTE_SIZE / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(down_tex, location, DOWN_ARROW_POS - NOTE_SIZE / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(NOTE_SIZE, NOTE_SIZE)),
                 ..Default::default()
             });
@@ -651,35 +1137,79 @@ pub fn draw_note_white(direction: f32, location: f32, left_tex: Texture2D, right
     }
 }
 
-pub fn draw_hold_white(direction: f32, location: f32, width: f32, texture: Texture2D, thickness_multi: f32) {
+pub fn draw_hold_tinted(direction: f32, location: f32, width: f32, texture: Texture2D, thickness_multi: f32, color: Color) {
     let direction = direction.round() as i32;
     let note_height = NOTE_SIZE * thickness_multi;
     let location = location + 20.0;
     match direction {
         1 => { // Right
-            draw_texture_ex(texture, location, RIGHT_ARROW_POS - note_height / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(texture, location, RIGHT_ARROW_POS - note_height / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(-width, note_height)),
                 ..Default::default()
             });
         },
         2 => { // Left
-            draw_texture_ex(texture, location, LEFT_ARROW_POS - note_height / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(texture, location, LEFT_ARROW_POS - note_height / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(-width, note_height)),
                 ..Default::default()
             });
         },
         3 => { // Up
-            draw_texture_ex(texture, location, UP_ARROW_POS - note_height / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(texture, location, UP_ARROW_POS - note_height / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(-width, note_height)),
                 ..Default::default()
             });
         },
         4 => { // Down
-            draw_texture_ex(texture, location, DOWN_ARROW_POS - note_height / 2.0, WHITE, DrawTextureParams {
+            draw_texture_ex(texture, location, DOWN_ARROW_POS - note_height / 2.0, color, DrawTextureParams {
                 dest_size: Some(vec2(-width, note_height)),
                 ..Default::default()
             });
         },
         _ => { todo!("Add direction drawing for note type.") }
     }
-}
\ No newline at end of file
+}
+
+pub fn lane_combo_box(ui: &mut egui::Ui, label: &str, lane: &mut f32) {
+    egui::ComboBox::from_label(label)
+        .selected_text(lane_name(*lane))
+        .show_ui(ui, |ui| {
+            for option in LANES_TOP_TO_BOTTOM {
+                ui.selectable_value(lane, option, lane_name(option));
+            }
+        });
+}
+
+/// One of each kind of special note at `beat`, in the order the editor lists them
+fn special_note_templates(beat: f32) -> Vec<SpecialNote> {
+    vec![
+        SpecialNote::Mine { beat, lane: 1.0 },
+        SpecialNote::Chord { beat, lanes: vec![2.0, 1.0] },
+        SpecialNote::Roll { beat, lane: 1.0, length: 2.0 },
+        SpecialNote::ShipTarget { beat, lane: 1.0 }
+    ]
+}
+
+/// One of each kind of special attack at `beat`, in the order the editor lists them
+fn special_attack_templates(beat: f32) -> Vec<Attack> {
+    let attack = |kind: AttackKind| Attack { beat, length: 4.0, warmup: ATTACK_WARMUP_BEATS, kind };
+    vec![
+        attack(AttackKind::Laser { lane: 1.0 }),
+        attack(AttackKind::MovingLaser { from_lane: 2.0, to_lane: 4.0 }),
+        attack(AttackKind::Projectiles { lane: 1.0, count: 4 }),
+        attack(AttackKind::Barrage { gap_lane: 1.0 })
+    ]
+}
+
+/// Shift-click adds to or removes from the selection, a plain click selects only that item
+fn toggle_selection(selection: &mut Vec<usize>, idx: usize) {
+    if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+        if let Some(position) = selection.iter().position(|x| *x == idx) {
+            selection.remove(position);
+        } else {
+            selection.push(idx);
+        }
+    } else {
+        *selection = vec![idx];
+    }
+}
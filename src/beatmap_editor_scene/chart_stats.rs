@@ -0,0 +1,96 @@
+use crate::note_gameplay_scene::scoring::ScoringRules;
+use crate::note_gameplay_scene::song::{Song, LANES_TOP_TO_BOTTOM};
+use crate::note_gameplay_scene::special_notes::scored_notes;
+
+// Width of the window notes per second are averaged over, in seconds
+const NPS_WINDOW: f32 = 4.0;
+
+// Upper bounds of the estimated rating for each difficulty folder, each about halfway between the hardest
+// shipped chart in a folder and the easiest in the next. Forest Lullaby and Goldn rate 0.74-0.89 on easy,
+// 1.63-2.00 on medium, 2.77-2.81 on hard, 4.04-4.76 on expert and 4.96-5.63 on extreme.
+const DIFFICULTY_THRESHOLDS: [(f32, &str); 4] = [
+    (1.2, "Easy"),
+    (2.3, "Medium"),
+    (3.4, "Hard"),
+    (4.9, "Expert")
+];
+
+pub struct ChartStats {
+    // Ordered like LANES_TOP_TO_BOTTOM
+    pub lane_counts: [usize; 4],
+    pub hold_count: usize,
+    pub total_hold_beats: f32,
+    pub attack_count: usize,
+    pub special_note_count: usize,
+    // Seconds, notes per second
+    pub nps_over_time: Vec<[f64; 2]>,
+    pub average_nps: f32,
+    pub peak_nps: f32,
+    pub max_combo: usize,
+    pub max_score: i32,
+    pub rating: f32
+}
+
+impl ChartStats {
+    pub fn new(song: &Song) -> Self {
+        let beats_per_second = song.bpm / 60.0;
+        let scoring = ScoringRules::for_song(song);
+        let mut scored = song.notes.clone();
+        scored.extend(scored_notes(&song.special_notes));
+
+        let mut lane_counts = [0; 4];
+        for note in &song.notes {
+            if let Some(idx) = LANES_TOP_TO_BOTTOM.iter().position(|x| *x == note.1) {
+                lane_counts[idx] += 1;
+            }
+        }
+
+        let holds = song.notes.iter().filter(|x| x.2 > 0.0);
+        let hold_count = holds.clone().count();
+        let total_hold_beats = holds.map(|x| x.2).sum();
+
+        let mut note_times: Vec<f32> = song.notes.iter().map(|x| x.0 / beats_per_second).collect();
+        note_times.sort_by(|a, b| a.total_cmp(b));
+
+        let notes_in_window = |start: f32| {
+            let first = note_times.partition_point(|x| *x < start);
+            let last = note_times.partition_point(|x| *x <= start + NPS_WINDOW);
+            (last - first) as f32 / NPS_WINDOW
+        };
+
+        let nps_over_time = (0..=song.song_length.ceil() as usize)
+            .map(|second| [second as f64, notes_in_window(second as f32 - NPS_WINDOW / 2.0) as f64])
+            .collect();
+        let peak_nps = note_times.iter().map(|x| notes_in_window(*x)).fold(0.0, f32::max);
+        let average_nps = match song.song_length > 0.0 {
+            true => song.notes.len() as f32 / song.song_length,
+            false => 0.0
+        };
+
+        let attacks_per_minute = match song.song_length > 0.0 {
+            true => (song.attacks.len() + song.special_attacks.len()) as f32 / song.song_length * 60.0,
+            false => 0.0
+        };
+
+        Self {
+            lane_counts,
+            hold_count,
+            total_hold_beats,
+            attack_count: song.attacks.len() + song.special_attacks.len(),
+            special_note_count: song.special_notes.len(),
+            nps_over_time,
+            average_nps,
+            peak_nps,
+            max_combo: scoring.perfect_judgements(&scored).len(),
+            max_score: scoring.max_score(&scored),
+            rating: 0.6 * average_nps + 0.4 * peak_nps + 0.03 * attacks_per_minute
+        }
+    }
+
+    pub fn difficulty_name(&self) -> &'static str {
+        DIFFICULTY_THRESHOLDS.iter()
+            .find(|(threshold, _)| self.rating < *threshold)
+            .map(|(_, name)| *name)
+            .unwrap_or("Extreme")
+    }
+}
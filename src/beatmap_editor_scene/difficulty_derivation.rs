@@ -0,0 +1,175 @@
+use crate::beatmap_editor_scene::laser_patterns::find_impossible_sections;
+use crate::note_gameplay_scene::attacks::Attack;
+use crate::note_gameplay_scene::judgement_log::Lamp;
+use crate::note_gameplay_scene::song::Song;
+use crate::note_gameplay_scene::special_notes::SpecialNote;
+
+// Hold notes shorter than this after trimming become taps
+const MIN_DERIVED_HOLD: f32 = 0.5;
+
+// Difficulty folders in assets/songs, easiest first
+pub const DIFFICULTY_ORDER: [&str; 5] = ["easy", "medium", "hard", "expert", "extreme"];
+
+#[derive(Clone, Copy, Debug)]
+pub struct DerivationSettings {
+    // Share of the master chart's notes that are kept
+    pub note_density: f32,
+    // Closest two kept notes can be, in beats
+    pub min_spacing: f32,
+    // Longest a hold or roll can be, in beats, 0 removes holds
+    pub max_hold: f32,
+    // Most lanes a chord can ask for at once
+    pub max_chord_lanes: usize,
+    // Share of the master chart's attacks that are kept
+    pub laser_density: f32
+}
+
+impl DerivationSettings {
+    /// Settings for deriving `difficulty` from an extreme chart.
+    /// The densities are Goldn's note and attack counts in each folder over its extreme chart's (105, 194, 326 and 481 of 644 notes),
+    /// the spacing halves each step down to the eighth of a beat the extreme charts use, and the hold and chord limits are picked by hand.
+    pub fn for_difficulty(difficulty: &str) -> Self {
+        match difficulty {
+            "easy" => Self { note_density: 0.16, min_spacing: 1.0, max_hold: 2.0, max_chord_lanes: 1, laser_density: 0.15 },
+            "medium" => Self { note_density: 0.3, min_spacing: 0.5, max_hold: 4.0, max_chord_lanes: 2, laser_density: 0.28 },
+            "hard" => Self { note_density: 0.5, min_spacing: 0.25, max_hold: 8.0, max_chord_lanes: 3, laser_density: 0.47 },
+            "expert" => Self { note_density: 0.75, min_spacing: 0.125, max_hold: f32::MAX, max_chord_lanes: 4, laser_density: 0.74 },
+            _ => Self { note_density: 1.0, min_spacing: 0.0, max_hold: f32::MAX, max_chord_lanes: 4, laser_density: 1.0 }
+        }
+    }
+
+    /// Scales the extreme-relative settings so they apply to a master chart of another difficulty
+    pub fn relative_to(self, master: Self) -> Self {
+        Self {
+            note_density: (self.note_density / master.note_density).min(1.0),
+            min_spacing: self.min_spacing,
+            max_hold: self.max_hold,
+            max_chord_lanes: self.max_chord_lanes,
+            laser_density: (self.laser_density / master.laser_density).min(1.0)
+        }
+    }
+}
+
+/// Difficulty folders easier than `master`
+pub fn easier_difficulties(master: &str) -> Vec<&'static str> {
+    DIFFICULTY_ORDER.iter()
+        .take_while(|x| **x != master)
+        .copied()
+        .collect()
+}
+
+/// How strongly a beat falls on the song's pulse, measure starts (downbeats) score highest
+pub fn beat_importance(beat: f32) -> u32 {
+    let is_multiple = |step: f32| (beat / step - (beat / step).round()).abs() < 0.001;
+
+    if is_multiple(4.0) {
+        5
+    } else if is_multiple(2.0) {
+        4
+    } else if is_multiple(1.0) {
+        3
+    } else if is_multiple(0.5) {
+        2
+    } else if is_multiple(0.25) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Builds an easier copy of `master`, keeping the most important notes first
+pub fn derive_chart(master: &Song, settings: DerivationSettings) -> Song {
+    let mut derived = master.clone();
+    derived.high_score = 0;
+    derived.modded_high_scores.clear();
+    derived.best_grade = None;
+    derived.lamp = Lamp::NotPlayed;
+    // Easier charts get the health rules of the folder they're saved in
+    derived.health_preset = None;
+
+    // Notes
+    let target_notes = (master.notes.len() as f32 * settings.note_density).round() as usize;
+
+    let mut candidates = master.notes.clone();
+    candidates.sort_by(|a, b| {
+        let importance = |note: &(f32, f32, f32)| beat_importance(note.0) + (note.2 > 0.0) as u32;
+        importance(b).cmp(&importance(a)).then(a.0.total_cmp(&b.0))
+    });
+
+    let mut notes: Vec<(f32, f32, f32)> = vec![];
+    for note in candidates {
+        if notes.len() >= target_notes {
+            break;
+        }
+        if notes.iter().any(|x| (x.0 - note.0).abs() < settings.min_spacing) {
+            continue;
+        }
+        notes.push(note);
+    }
+    notes.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Holds can't run into the next note in their lane
+    for i in 0..notes.len() {
+        let (note_beat, note_type, hold_length) = notes[i];
+        let next_in_lane = notes[i + 1..].iter()
+            .find(|x| x.1 == note_type)
+            .map(|x| x.0 - settings.min_spacing.max(0.25))
+            .unwrap_or(f32::MAX);
+
+        let mut hold_length = hold_length.min(settings.max_hold).min(next_in_lane - note_beat);
+        if hold_length < MIN_DERIVED_HOLD {
+            hold_length = 0.0;
+        }
+        notes[i].2 = hold_length;
+    }
+
+    // Special notes, thinned the same way and kept apart from the notes above
+    let target_special_notes = (master.special_notes.len() as f32 * settings.note_density).round() as usize;
+
+    let mut special_candidates = master.special_notes.clone();
+    special_candidates.sort_by(|a, b| {
+        beat_importance(b.beat()).cmp(&beat_importance(a.beat())).then(a.beat().total_cmp(&b.beat()))
+    });
+
+    let mut special_notes: Vec<SpecialNote> = vec![];
+    for mut special_note in special_candidates {
+        if special_notes.len() >= target_special_notes {
+            break;
+        }
+        let beat = special_note.beat();
+        if notes.iter().map(|x| x.0).chain(special_notes.iter().map(|x| x.beat())).any(|x| (x - beat).abs() < settings.min_spacing) {
+            continue;
+        }
+
+        match &mut special_note {
+            SpecialNote::Chord { lanes, .. } => lanes.truncate(settings.max_chord_lanes),
+            SpecialNote::Roll { length, .. } => *length = length.min(settings.max_hold),
+            _ => {}
+        }
+        special_notes.push(special_note);
+    }
+    special_notes.sort_by(|a, b| a.beat().total_cmp(&b.beat()));
+
+    derived.notes = notes;
+    derived.special_notes = special_notes;
+
+    // Attacks, spread evenly through the song
+    let mut attacks = master.attacks.clone();
+    attacks.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut kept_attacks: Vec<(f32, f32, f32)> = attacks.iter().enumerate()
+        .filter(|(i, _)| (*i as f32 * settings.laser_density).floor() != ((i + 1) as f32 * settings.laser_density).floor())
+        .map(|(_, x)| *x)
+        .collect();
+
+    // Dropping lasers only opens lanes up, but anything already impossible in the master stays impossible.
+    // Special attacks are kept as they are, so lasers are dropped around them instead.
+    let all_attacks: Vec<Attack> = kept_attacks.iter()
+        .map(Attack::from_legacy)
+        .chain(master.special_attacks.iter().copied())
+        .collect();
+    let impossible_sections = find_impossible_sections(&all_attacks, master.bpm);
+    kept_attacks.retain(|attack| !impossible_sections.iter().any(|x| x.overlaps_attack(&Attack::from_legacy(attack))));
+    derived.attacks = kept_attacks;
+
+    derived
+}
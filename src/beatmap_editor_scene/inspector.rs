@@ -0,0 +1,175 @@
+use egui_macroquad::egui;
+use crate::note_gameplay_scene::attacks::Attack;
+use crate::note_gameplay_scene::constants::ATTACK_WARMUP_BEATS;
+use crate::note_gameplay_scene::song::{lane_name, LANES_TOP_TO_BOTTOM};
+
+// Attacks shorter than this can't be seen before they're over
+const MIN_ATTACK_LENGTH: f32 = 0.125;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InspectorKind {
+    Note,
+    Attack
+}
+
+/// A change made in the inspector, applied to every selected item
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InspectorEdit {
+    Move(f32),
+    Lane(f32),
+    Length(f32)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct InspectorResponse {
+    pub edit: Option<InspectorEdit>,
+    // True while a field is being dragged or typed into, so one drag only makes one undo step
+    pub editing: bool
+}
+
+/// Converts an attack (Beat, Last Time, Type) to the inspector's (Beat, Lane, Length) layout
+pub fn attack_fields(attack: &(f32, f32, f32)) -> (f32, f32, f32) {
+    (attack.0, attack.2, attack.1)
+}
+
+pub fn apply_note_edit(note: &mut (f32, f32, f32), edit: InspectorEdit) {
+    match edit {
+        InspectorEdit::Move(offset) => note.0 = (note.0 + offset).max(0.0),
+        InspectorEdit::Lane(lane) => note.1 = lane,
+        InspectorEdit::Length(length) => note.2 = length.max(0.0)
+    }
+}
+
+pub fn apply_attack_edit(attack: &mut (f32, f32, f32), edit: InspectorEdit) {
+    match edit {
+        InspectorEdit::Move(offset) => attack.0 = (attack.0 + offset).max(0.0),
+        InspectorEdit::Lane(lane) => attack.2 = lane,
+        InspectorEdit::Length(length) => attack.1 = length.max(MIN_ATTACK_LENGTH)
+    }
+}
+
+/// Draws labeled fields for the selected notes or attacks. `items` holds every note/attack
+/// in (Beat, Lane, Length) layout so overlaps can be checked against the rest of the chart.
+pub fn inspector(ui: &mut egui::Ui, kind: InspectorKind, items: &[(f32, f32, f32)], selected: &[usize], beats_per_second: f32, song_length: f32) -> InspectorResponse {
+    let mut response = InspectorResponse::default();
+
+    let Some(first) = selected.first() else {
+        return response;
+    };
+    let (first_beat, first_lane, first_length) = items[*first];
+
+    let (beat_label, length_label) = match kind {
+        InspectorKind::Note => ("Beat", "Hold Length"),
+        InspectorKind::Attack => ("Start", "Duration")
+    };
+    let min_length = match kind {
+        InspectorKind::Note => 0.0,
+        InspectorKind::Attack => MIN_ATTACK_LENGTH
+    };
+
+    if selected.len() > 1 {
+        ui.label(format!("{} selected", selected.len()));
+    }
+
+    egui::Grid::new(match kind {
+        InspectorKind::Note => "note_inspector",
+        InspectorKind::Attack => "attack_inspector"
+    }).num_columns(2).show(ui, |ui| {
+        // With several items selected, moving the first one moves them all by the same amount
+        let lowest_beat = selected.iter().map(|x| items[*x].0).fold(f32::MAX, f32::min);
+        let mut beat = first_beat;
+        ui.label(beat_label);
+        let beat_response = ui.add(egui::DragValue::new(&mut beat)
+            .speed(0.0625)
+            .clamp_range(first_beat - lowest_beat..=f32::MAX));
+        if beat_response.changed() {
+            response.edit = Some(InspectorEdit::Move(beat - first_beat));
+        }
+        response.editing |= beat_response.dragged() || beat_response.has_focus();
+        ui.end_row();
+
+        ui.label("Time");
+        ui.label(format!("{:.3}s", first_beat / beats_per_second));
+        ui.end_row();
+
+        let mixed_lanes = selected.iter().any(|x| items[*x].1 != first_lane);
+        let mut lane = if mixed_lanes { 0.0 } else { first_lane };
+        ui.label("Lane");
+        egui::ComboBox::from_id_source(beat_label)
+            .selected_text(if mixed_lanes { "Mixed" } else { lane_name(lane) })
+            .show_ui(ui, |ui| {
+                for option in LANES_TOP_TO_BOTTOM {
+                    ui.selectable_value(&mut lane, option, lane_name(option));
+                }
+            });
+        if lane != 0.0 && (mixed_lanes || lane != first_lane) {
+            response.edit = Some(InspectorEdit::Lane(lane));
+        }
+        ui.end_row();
+
+        let mut length = first_length;
+        ui.label(length_label);
+        let length_response = ui.add(egui::DragValue::new(&mut length)
+            .speed(0.0625)
+            .clamp_range(min_length..=f32::MAX)
+            .suffix(format!(" ({:.2}s)", first_length / beats_per_second)));
+        if length_response.changed() {
+            response.edit = Some(InspectorEdit::Length(length));
+        }
+        response.editing |= length_response.dragged() || length_response.has_focus();
+        ui.end_row();
+    });
+
+    for warning in validate(kind, items, selected, beats_per_second, song_length) {
+        ui.colored_label(egui::Color32::from_rgb(255, 160, 60), warning);
+    }
+
+    response
+}
+
+/// Lists everything wrong with the selected items that the fields alone can't prevent
+pub fn validate(kind: InspectorKind, items: &[(f32, f32, f32)], selected: &[usize], beats_per_second: f32, song_length: f32) -> Vec<String> {
+    let mut warnings = vec![];
+    let song_beats = song_length * beats_per_second;
+
+    for idx in selected {
+        let (beat, lane, length) = items[*idx];
+
+        if lane_name(lane) == "Unknown" {
+            warnings.push(format!("Beat {beat:.3}: lane '{lane}' doesn't exist"));
+        }
+        if beat + length > song_beats {
+            warnings.push(format!("Beat {beat:.3}: ends after the song is over"));
+        }
+
+        match kind {
+            InspectorKind::Note => {
+                let overlapping = items.iter().enumerate().any(|(other_idx, (other_beat, other_lane, other_length))| {
+                    other_idx != *idx && *other_lane == lane
+                        && beat <= other_beat + other_length && *other_beat <= beat + length
+                });
+                if overlapping {
+                    warnings.push(format!("Beat {beat:.3}: overlaps another note in the {} lane", lane_name(lane)));
+                }
+            }
+            InspectorKind::Attack => {
+                if beat < ATTACK_WARMUP_BEATS {
+                    warnings.push(format!("Beat {beat:.3}: starts before its warm-up can be shown"));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// The warnings for a special attack, which has its own warm-up instead of the legacy one
+pub fn validate_special_attack(attack: &Attack) -> Vec<String> {
+    let mut warnings = vec![];
+
+    if attack.beat < attack.warmup {
+        warnings.push(format!("Beat {:.3}: starts before its warm-up can be shown", attack.beat));
+    }
+
+    warnings
+}
@@ -0,0 +1,171 @@
+use crate::note_gameplay_scene::attacks::Attack;
+use crate::note_gameplay_scene::constants::{RIGHT_ARROW_POS, SHIP_LANE_MOVE_TIME};
+use crate::note_gameplay_scene::song::{lane_position, LANES_TOP_TO_BOTTOM};
+use crate::note_gameplay_scene::ship_controls::cycle_step;
+
+// How finely the feasibility check samples the chart, in beats
+const FEASIBILITY_STEP: f32 = 1.0 / 16.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LaserPattern {
+    Sweep,
+    Alternate,
+    DoubleLane
+}
+
+impl LaserPattern {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LaserPattern::Sweep => "Sweep",
+            LaserPattern::Alternate => "Alternate",
+            LaserPattern::DoubleLane => "Double Lane"
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PatternSettings {
+    pub pattern: LaserPattern,
+    pub count: usize,
+    pub spacing: f32,
+    pub length: f32,
+    pub first_lane: f32,
+    pub second_lane: f32
+}
+
+impl Default for PatternSettings {
+    fn default() -> Self {
+        Self {
+            pattern: LaserPattern::Sweep,
+            count: 4,
+            spacing: 1.0,
+            length: 1.0,
+            first_lane: 2.0,
+            second_lane: 4.0
+        }
+    }
+}
+
+/// Builds the attacks (Beat, Last Time, Type) for a pattern starting at `start_beat`
+pub fn generate_pattern(settings: &PatternSettings, start_beat: f32) -> Vec<(f32, f32, f32)> {
+    let mut attacks = vec![];
+
+    let first_idx = LANES_TOP_TO_BOTTOM.iter().position(|x| *x == settings.first_lane).unwrap_or(0);
+
+    for i in 0..settings.count {
+        let beat = start_beat + settings.spacing * i as f32;
+
+        match settings.pattern {
+            LaserPattern::Sweep => {
+                // Bounce between the top and bottom lanes
+                let step = (first_idx + i) % 6;
+                let idx = if step < 4 { step } else { 6 - step };
+                attacks.push((beat, settings.length, LANES_TOP_TO_BOTTOM[idx]));
+            }
+            LaserPattern::Alternate => {
+                let lane = match i % 2 {
+                    0 => settings.first_lane,
+                    _ => settings.second_lane
+                };
+                attacks.push((beat, settings.length, lane));
+            }
+            LaserPattern::DoubleLane => {
+                attacks.push((beat, settings.length, settings.first_lane));
+                if settings.second_lane != settings.first_lane {
+                    attacks.push((beat, settings.length, settings.second_lane));
+                }
+            }
+        }
+    }
+
+    attacks
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ImpossibleSection {
+    pub start_beat: f32,
+    pub end_beat: f32
+}
+
+impl ImpossibleSection {
+    pub fn overlaps_attack(&self, attack: &Attack) -> bool {
+        attack.beat <= self.end_beat && attack.end_beat() >= self.start_beat
+    }
+}
+
+/// Walks through the chart and finds every stretch where the ship can't be in any safe lane,
+/// either because every lane is covered or because the ship can't get to the open lane in time.
+pub fn find_impossible_sections(attacks: &[Attack], bpm: f32) -> Vec<ImpossibleSection> {
+    let mut sections: Vec<ImpossibleSection> = vec![];
+
+    if attacks.is_empty() {
+        return sections;
+    }
+
+    let beats_per_second = bpm / 60.0;
+    let move_beats = SHIP_LANE_MOVE_TIME * beats_per_second;
+
+    let last_beat = attacks.iter().map(|x| x.end_beat()).fold(0.0, f32::max);
+
+    // The beat since which the ship could be sitting safely in each lane
+    let mut reachable_since: [Option<f32>; 4] = [None; 4];
+    for (idx, lane) in LANES_TOP_TO_BOTTOM.iter().enumerate() {
+        if lane_position(*lane) == RIGHT_ARROW_POS {
+            reachable_since[idx] = Some(f32::MIN);
+        }
+    }
+
+    let mut beat = 0.0;
+    while beat <= last_beat {
+        let blocked: Vec<bool> = LANES_TOP_TO_BOTTOM.iter()
+            .map(|lane| attacks.iter().any(|x| x.blocks(beat, lane_position(*lane))))
+            .collect();
+
+        // After a forced hit the ship could be anywhere, so every open lane counts again
+        let recovering = reachable_since.iter().all(|x| x.is_none());
+
+        let mut next = reachable_since;
+        for idx in 0..LANES_TOP_TO_BOTTOM.len() {
+            if blocked[idx] {
+                next[idx] = None;
+                continue;
+            }
+
+            if recovering {
+                next[idx] = Some(beat);
+            }
+
+            if next[idx].is_some() {
+                continue;
+            }
+
+            let position = lane_position(LANES_TOP_TO_BOTTOM[idx]);
+            for up in [true, false] {
+                let Some(location) = cycle_step(position, up) else {
+                    continue;
+                };
+
+                let neighbour = LANES_TOP_TO_BOTTOM.iter().position(|x| lane_position(*x) == location).unwrap();
+                if let Some(since) = reachable_since[neighbour] {
+                    if since + move_beats <= beat {
+                        next[idx] = Some(beat);
+                    }
+                }
+            }
+        }
+
+        if next.iter().all(|x| x.is_none()) {
+            match sections.last_mut() {
+                Some(section) if beat - section.end_beat <= FEASIBILITY_STEP * 1.5 => {
+                    section.end_beat = beat;
+                }
+                _ => sections.push(ImpossibleSection { start_beat: beat, end_beat: beat })
+            }
+        }
+
+        reachable_since = next;
+        beat += FEASIBILITY_STEP;
+    }
+
+    sections
+}
@@ -0,0 +1,117 @@
+use macroquad::prelude::*;
+use crate::input::{Binding, Controls, Input};
+
+// Held notes shorter than this are recorded as plain taps
+const MIN_RECORDED_HOLD: f32 = 0.5;
+
+// Snap divisions offered in the editor, in beats
+pub const SNAP_OPTIONS: [(f32, &str); 7] = [
+    (1.0, "1/1"),
+    (0.5, "1/2"),
+    (1.0 / 3.0, "1/3"),
+    (0.25, "1/4"),
+    (1.0 / 6.0, "1/6"),
+    (0.125, "1/8"),
+    (0.0625, "1/16")
+];
+
+/// Turns lane key presses into notes while the song plays. Recorded notes are kept
+/// in their own layer until they're merged into the chart or thrown away.
+pub struct Recorder {
+    pub recording: bool,
+    pub snap: f32,
+    // Beat, Type, Hold Length
+    pub layer: Vec<(f32, f32, f32)>,
+    // Lane bindings and the note type they place
+    lane_bindings: [(Vec<Binding>, f32); 4],
+    // The recorded note each lane key is still holding down
+    held: [Option<usize>; 4]
+}
+
+impl Recorder {
+    pub fn new(controls: &Controls) -> Self {
+        Self {
+            recording: false,
+            snap: 0.25,
+            layer: vec![],
+            lane_bindings: [
+                (controls.right_arrow.clone(), 1.0),
+                (controls.left_arrow.clone(), 2.0),
+                (controls.up_arrow.clone(), 3.0),
+                (controls.down_arrow.clone(), 4.0)
+            ],
+            held: [None; 4]
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.recording = true;
+        self.held = [None; 4];
+    }
+
+    pub fn stop(&mut self, beat: f32) {
+        for lane in 0..self.held.len() {
+            self.release(lane, beat);
+        }
+        self.recording = false;
+    }
+
+    pub fn snap_name(&self) -> &'static str {
+        SNAP_OPTIONS.iter().find(|x| x.0 == self.snap).map(|x| x.1).unwrap_or("Custom")
+    }
+
+    pub fn quantize(&self, beat: f32) -> f32 {
+        (beat / self.snap).round() * self.snap
+    }
+
+    /// Polls the lane bindings, call once per frame while recording
+    pub fn update(&mut self, beat: f32, input: &Input) {
+        if !self.recording {
+            return;
+        }
+
+        for lane in 0..self.lane_bindings.len() {
+            let (bindings, note_type) = &self.lane_bindings[lane];
+            let note_type = *note_type;
+
+            if input.is_pressed(bindings) {
+                self.release(lane, beat);
+                self.layer.push((self.quantize(beat), note_type, 0.0));
+                self.held[lane] = Some(self.layer.len() - 1);
+            } else if !input.is_down(bindings) {
+                self.release(lane, beat);
+            }
+        }
+    }
+
+    fn release(&mut self, lane: usize, beat: f32) {
+        if let Some(idx) = self.held[lane].take() {
+            let hold_length = self.quantize(beat) - self.layer[idx].0;
+            if hold_length >= MIN_RECORDED_HOLD {
+                self.layer[idx].2 = hold_length;
+            }
+        }
+    }
+
+    /// Moves the recorded layer into `notes`, skipping notes already in the chart.
+    /// Returns the indices of the merged notes.
+    pub fn merge_into(&mut self, notes: &mut Vec<(f32, f32, f32)>) -> Vec<usize> {
+        let mut merged = vec![];
+
+        for note in self.layer.drain(..) {
+            if notes.iter().any(|x| x.0 == note.0 && x.1 == note.1) {
+                continue;
+            }
+            notes.push(note);
+            merged.push(notes.len() - 1);
+        }
+
+        self.held = [None; 4];
+        merged
+    }
+
+    pub fn discard(&mut self) {
+        self.layer.clear();
+        self.held = [None; 4];
+    }
+}
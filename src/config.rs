@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::input::{legacy_key_code, Binding, Controls};
+use crate::note_gameplay_scene::constants::{MAX_SCROLL_SPEED, MIN_SCROLL_SPEED};
+use crate::note_gameplay_scene::ghost_taps::GhostTapPolicy;
+use crate::note_gameplay_scene::ship_controls::ShipControlScheme;
+
+// Bumped whenever a saved field changes shape, see migrate
+pub const CONFIG_VERSION: u32 = 2;
+pub const MAX_RESOLUTION_SCALE: u32 = 16;
+
+// The config that ships with the game, used to make a player's own config the first time the game runs
+const DEFAULT_CONFIG_PATH: &str = "assets/config.json";
+const CONFIG_FOLDER: &str = "TheBeatOfSpace";
+const CONFIG_FILE: &str = "config.json";
+
+/// Any field missing from a saved config takes its value from Config::default
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    pub music_volume: f64,
+    // Hitsounds, lasers and menu clicks
+    pub sfx_volume: f64,
+    pub fullscreen: bool,
+    pub resolution_scale: u32,
+    pub controls: Controls,
+    // Multiplies how fast notes scroll towards the arrows
+    pub scroll_speed: f32,
+    // Notes take the same time to arrive at any BPM instead of the same number of beats
+    pub constant_time: bool,
+    // Whether pressing a lane with no note to hit costs health
+    pub ghost_taps: GhostTapPolicy,
+    // How the ship is moved between lanes
+    pub ship_controls: ShipControlScheme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            fullscreen: true,
+            resolution_scale: 4,
+            controls: Controls::default(),
+            scroll_speed: 1.0,
+            constant_time: false,
+            ghost_taps: GhostTapPolicy::default(),
+            ship_controls: ShipControlScheme::default()
+        }
+    }
+}
+
+impl Config {
+    /// Pulls every value back into the range the game supports
+    pub fn validated(self) -> Self {
+        let defaults = Config::default();
+
+        Self {
+            version: CONFIG_VERSION,
+            music_volume: match self.music_volume.is_finite() {
+                true => self.music_volume.clamp(0.0, 1.0),
+                false => defaults.music_volume
+            },
+            sfx_volume: match self.sfx_volume.is_finite() {
+                true => self.sfx_volume.clamp(0.0, 1.0),
+                false => defaults.sfx_volume
+            },
+            resolution_scale: self.resolution_scale.clamp(1, MAX_RESOLUTION_SCALE),
+            scroll_speed: match self.scroll_speed.is_finite() {
+                true => self.scroll_speed.clamp(MIN_SCROLL_SPEED, MAX_SCROLL_SPEED),
+                false => defaults.scroll_speed
+            },
+            ..self
+        }
+    }
+
+    /// Reads a saved config, bringing it up to the current version first
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let mut value: Value = serde_json::from_str(json)?;
+        migrate(&mut value);
+
+        Ok(serde_json::from_value::<Config>(value)?.validated())
+    }
+}
+
+/// Updates the fields of a saved config that changed shape since it was saved.
+/// Configs saved before versions were added count as version 0.
+fn migrate(value: &mut Value) {
+    let version = value.get("version").and_then(|x| x.as_u64()).unwrap_or(0);
+
+    // 0 -> 1: Each control was a single key number, now it's a list of named keys and gamepad inputs
+    if version < 1 {
+        if let Some(controls) = value.get_mut("controls").and_then(|x| x.as_object_mut()) {
+            let defaults = Controls::default();
+            for (action, default_bindings) in [
+                ("up_arrow", defaults.up_arrow),
+                ("down_arrow", defaults.down_arrow),
+                ("left_arrow", defaults.left_arrow),
+                ("right_arrow", defaults.right_arrow),
+                ("ship_up", defaults.ship_up),
+                ("ship_down", defaults.ship_down)
+            ] {
+                let Some(code) = controls.get(action).and_then(|x| x.as_u64()) else {
+                    continue;
+                };
+
+                // The old key replaces the default key, and the gamepad gets its default bindings
+                let mut bindings: Vec<Binding> = default_bindings.into_iter()
+                    .filter(|x| !matches!(x, Binding::Key(_)))
+                    .collect();
+                if let Some(binding) = legacy_key_code(code as u32).and_then(Binding::key) {
+                    bindings.insert(0, binding);
+                }
+
+                controls.insert(action.to_string(), serde_json::to_value(bindings).unwrap_or_default());
+            }
+        }
+    }
+
+    // 1 -> 2: The one volume was split into music and sound effect volumes, both starting where it was
+    if version < 2 {
+        if let Some(object) = value.as_object_mut() {
+            if let Some(volume) = object.remove("volume") {
+                object.insert("music_volume".to_string(), volume.clone());
+                object.insert("sfx_volume".to_string(), volume);
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), Value::from(CONFIG_VERSION));
+    }
+}
+
+/// Where the player's config is saved, in their own config folder so it survives reinstalling the game
+fn config_path() -> PathBuf {
+    match dirs::config_dir() {
+        Some(dir) => dir.join(CONFIG_FOLDER).join(CONFIG_FILE),
+        None => PathBuf::from(DEFAULT_CONFIG_PATH)
+    }
+}
+
+/// The player's config, the one shipped with the game if they don't have one yet, or the defaults.
+/// A config that can't be read is kept next to the new one instead of being written over.
+pub fn load_config() -> Config {
+    let path = config_path();
+
+    if let Ok(json) = fs::read_to_string(&path) {
+        match Config::from_json(&json) {
+            Ok(config) => return config,
+            Err(_) => {
+                let _ = fs::copy(&path, path.with_extension("broken.json"));
+            }
+        }
+    }
+
+    let config = fs::read_to_string(DEFAULT_CONFIG_PATH).ok()
+        .and_then(|x| Config::from_json(&x).ok())
+        .unwrap_or_default();
+    write(&config);
+
+    config
+}
+
+fn write(config: &Config) {
+    let path = config_path();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(config) {
+        let _ = fs::write(path, json);
+    }
+}
+
+/// Validates and saves `config`, returning what was saved
+pub fn save_config(config: &Config) -> Config {
+    let validated = config.clone().validated();
+    write(&validated);
+
+    validated
+}
@@ -1,34 +1,32 @@
 use async_trait::async_trait;
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::WindowContext;
-use macroquad_aspect::window::draw_window;
+use crate::game_context::{Assets, GameContext};
 use crate::main_menu_scene::MainMenuScene;
-use crate::scene::Scene;
+use crate::scene::{Scene, SceneChange};
+use crate::transition::Transition;
 use crate::ui::draw_text_justified;
 
 pub struct ErrorScene {
-    error: String,
-    window_context: WindowContext
+    error: String
 }
 
 impl ErrorScene {
-    pub fn new(error: &str, window_context: WindowContext) -> Self {
+    pub fn new(error: &str) -> Self {
         Self {
-            error: error.to_string(),
-            window_context
+            error: error.to_string()
         }
     }
 }
 
 #[async_trait]
 impl Scene for ErrorScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
         loop {
-            set_camera(&self.window_context.camera);
+            set_camera(&ctx.window_context.camera);
 
             clear_background(BLACK);
             draw_text_justified(&format!("Error: {}", self.error),
-                                vec2(self.window_context.active_screen_size.x / 2.0, self.window_context.active_screen_size.y / 2.0),
+                                vec2(ctx.window_context.active_screen_size.x / 2.0, ctx.window_context.active_screen_size.y / 2.0),
                                 TextParams {
                                     font: Default::default(),
                                     font_size: 100,
@@ -38,7 +36,7 @@ impl Scene for ErrorScene {
                                 },
                                 vec2(0.5, 0.5));
             draw_text_justified("Space: Reload, Escape: Quit Game",
-                                vec2(self.window_context.active_screen_size.x / 2.0, self.window_context.active_screen_size.y / 2.0 + 150.0),
+                                vec2(ctx.window_context.active_screen_size.x / 2.0, ctx.window_context.active_screen_size.y / 2.0 + 150.0),
                                 TextParams {
                                     font: Default::default(),
                                     font_size: 50,
@@ -49,18 +47,22 @@ impl Scene for ErrorScene {
                                 vec2(0.5, 0.5));
 
             if is_key_pressed(KeyCode::Escape) {
-                return None
+                return SceneChange::Quit
             }
 
             if is_key_pressed(KeyCode::Space) {
-                return Some(Box::new(MainMenuScene {
-                    window_context: self.window_context.clone(),
-                    selected_difficulty: None,
-                    selected_song_idx: None,
-                }))
+                // Missing files may have been put back since they were last looked for
+                if !ctx.assets.missing.is_empty() {
+                    ctx.assets = Assets::load().await;
+                }
+
+                if ctx.assets.missing.is_empty() {
+                    return SceneChange::Reset(Box::new(MainMenuScene::default()), Transition::Fade)
+                }
+                self.error = format!("Assets Missing: {}", ctx.assets.missing.join(", "));
             }
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
 
             next_frame().await
         }
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use macroquad::prelude::*;
+use macroquad_aspect::prelude::{draw_window, WindowContext};
+use crate::audio::{Audio, Sfx};
+use crate::config::Config;
+use crate::input::Input;
+use crate::transition::{draw_render_target, ActiveTransition, Frame, Transition};
+use crate::ui::take_ui_sounds;
+use crate::utils::quick_load_texture;
+
+/// Everything scenes share, kept alive between them so moving to another scene doesn't load anything again
+pub struct GameContext {
+    pub window_context: WindowContext,
+    pub assets: Assets,
+    pub audio: Audio,
+    pub config: Config,
+    pub input: Input,
+    // A copy of the last frame shown, for transitions and overlays to draw
+    last_frame: Option<Frame>,
+    transition: Option<ActiveTransition>
+}
+
+impl GameContext {
+    pub async fn new(window_context: WindowContext, config: Config) -> Self {
+        Self {
+            window_context,
+            assets: Assets::load().await,
+            audio: Audio::new(&config),
+            config,
+            input: Input::new(),
+            last_frame: None,
+            transition: None
+        }
+    }
+
+    /// Starts `transition` from the last frame shown to whatever's drawn next
+    pub fn start_transition(&mut self, transition: Transition) {
+        self.transition = match transition {
+            Transition::None => None,
+            _ => self.last_frame.take().map(|frame| ActiveTransition::new(transition, frame))
+        };
+    }
+
+    /// The last frame shown, for an overlay to draw under itself while it's open
+    pub fn take_frame(&mut self) -> Option<Frame> {
+        self.last_frame.take()
+    }
+
+    /// Plays `sfx` if it was loaded
+    pub fn play_sfx(&mut self, sfx: Sfx) {
+        if let Some(sound) = self.assets.sounds.get(&sfx) {
+            self.audio.play_sfx(sound.clone());
+        }
+    }
+
+    /// Plays `sfx` sped up or slowed down to last `seconds`
+    pub fn play_sfx_lasting(&mut self, sfx: Sfx, seconds: f64) {
+        if let Some(sound) = self.assets.sounds.get(&sfx) {
+            let rate = sound.duration().as_secs_f64() / seconds.max(0.001);
+            self.audio.play_sfx(sound.with_modified_settings(|x| x.playback_rate(rate)));
+        }
+    }
+
+    /// Ends a scene's frame, drawing any transition over it and keeping a copy of it before it's shown
+    pub fn draw_window(&mut self) {
+        if let Some(transition) = self.transition.as_mut() {
+            if !transition.draw(&self.window_context) {
+                self.transition = None;
+            }
+        }
+
+        // Without a render target there's nothing to copy, so transitions and overlay backgrounds are skipped
+        if let Some(target) = self.window_context.camera.render_target {
+            let (width, height) = (target.texture.width() as u32, target.texture.height() as u32);
+            let frame = match self.last_frame.take() {
+                Some(frame) if frame.target().texture.width() as u32 == width && frame.target().texture.height() as u32 == height => frame,
+                _ => Frame::new(width, height)
+            };
+
+            set_camera(&Camera2D {
+                render_target: Some(frame.target()),
+                ..self.window_context.camera
+            });
+            clear_background(BLACK);
+            // Drawn the same way it's drawn back, so the copy comes out the right way up
+            draw_render_target(target, 0.0, WHITE, &self.window_context);
+            set_camera(&self.window_context.camera);
+
+            self.last_frame = Some(frame);
+        }
+
+        for sfx in take_ui_sounds() {
+            self.play_sfx(sfx);
+        }
+
+        draw_window(&mut self.window_context);
+    }
+}
+
+/// The textures and fonts every scene draws with, loaded once at start up
+#[derive(Clone)]
+pub struct Assets {
+    pub font: Font,
+
+    pub arrow_up: Texture2D,
+    pub arrow_down: Texture2D,
+    pub arrow_left: Texture2D,
+    pub arrow_right: Texture2D,
+    pub hold: Texture2D,
+    pub laser: Texture2D,
+    pub ship: Texture2D,
+
+    pub frame: Texture2D,
+    pub button: Texture2D,
+    pub plus: Texture2D,
+    pub minus: Texture2D,
+
+    pub gameplay_background: Texture2D,
+    pub results_background: Texture2D,
+    pub menu_background: Texture2D,
+
+    // Sound effects are small, so they're decoded up front to play without a delay
+    pub sounds: HashMap<Sfx, StaticSoundData>,
+
+    // Paths that couldn't be loaded, drawn as blank textures until they're reloaded
+    pub missing: Vec<String>
+}
+
+impl Assets {
+    pub async fn load() -> Self {
+        let mut missing = vec![];
+
+        let font = match load_ttf_font("assets/fonts/pixel.ttf").await {
+            Ok(font) => font,
+            Err(_) => {
+                missing.push("assets/fonts/pixel.ttf".to_string());
+                Font::default()
+            }
+        };
+
+        Self {
+            font,
+            arrow_up: texture_or_blank("assets/images/arrow_up.png", &mut missing).await,
+            arrow_down: texture_or_blank("assets/images/arrow_down.png", &mut missing).await,
+            arrow_left: texture_or_blank("assets/images/arrow_left.png", &mut missing).await,
+            arrow_right: texture_or_blank("assets/images/arrow_right.png", &mut missing).await,
+            hold: texture_or_blank("assets/images/hold.png", &mut missing).await,
+            laser: texture_or_blank("assets/images/laser.png", &mut missing).await,
+            ship: texture_or_blank("assets/images/ship.png", &mut missing).await,
+            frame: texture_or_blank("assets/images/ui/frame.png", &mut missing).await,
+            button: texture_or_blank("assets/images/ui/button.png", &mut missing).await,
+            plus: texture_or_blank("assets/images/ui/plus.png", &mut missing).await,
+            minus: texture_or_blank("assets/images/ui/minus.png", &mut missing).await,
+            gameplay_background: texture_or_blank("assets/images/backgrounds/Space Background (3).png", &mut missing).await,
+            results_background: texture_or_blank("assets/images/backgrounds/Space Background (9).png", &mut missing).await,
+            menu_background: texture_or_blank("assets/images/backgrounds/Space Background (15).png", &mut missing).await,
+            sounds: load_sounds(&mut missing),
+            missing
+        }
+    }
+}
+
+/// Every sound effect that could be loaded, the rest are noted in `missing`
+fn load_sounds(missing: &mut Vec<String>) -> HashMap<Sfx, StaticSoundData> {
+    let mut sounds = HashMap::new();
+
+    for sfx in Sfx::ALL {
+        match StaticSoundData::from_file(sfx.path(), StaticSoundSettings::default()) {
+            Ok(sound) => {
+                sounds.insert(sfx, sound);
+            }
+            Err(_) => missing.push(sfx.path().to_string())
+        }
+    }
+
+    sounds
+}
+
+/// A texture that fails to load is noted in `missing` and replaced with a blank one
+async fn texture_or_blank(path: &str, missing: &mut Vec<String>) -> Texture2D {
+    match quick_load_texture(path).await {
+        Ok(texture) => texture,
+        Err(_) => {
+            missing.push(path.to_string());
+            Texture2D::empty()
+        }
+    }
+}
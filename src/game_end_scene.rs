@@ -1,54 +1,41 @@
 use async_trait::async_trait;
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::{draw_window, WindowContext};
 use thousands::Separable;
-use crate::error_scene::ErrorScene;
-use crate::main_menu_scene::MainMenuScene;
-use crate::note_gameplay_scene::{NoteGameplayScene, ReturnTo};
+use crate::game_context::GameContext;
+use crate::note_gameplay_scene::NoteGameplayScene;
+use crate::note_gameplay_scene::health::HealthRules;
+use crate::note_gameplay_scene::judgement_log::{Judgement, JudgementLog};
+use crate::note_gameplay_scene::modifiers::{LaneModifier, Modifiers};
+use crate::note_gameplay_scene::song::{lane_name, LANES_TOP_TO_BOTTOM};
 use crate::ui::*;
-use crate::utils::quick_load_texture;
-use crate::scene::Scene;
+use crate::scene::{Scene, SceneChange};
+use crate::transition::Transition;
 
 pub struct GameEndScene {
-    pub return_to: ReturnTo,
-    pub window_context: WindowContext,
     pub file_path: String,
     pub beat_level: bool,
     pub score: i32,
-    pub perfect_notes: i32,
-    pub good_notes: i32,
-    pub ok_notes: i32,
-    pub incorrect_notes: i32,
-    pub missed_notes: i32
+    pub judgements: JudgementLog,
+    pub health_rules: HealthRules,
+    pub modifiers: Modifiers
 }
 
+// Slices the timing histogram is split into
+const HISTOGRAM_BINS: usize = 21;
+
 #[async_trait]
 impl Scene for GameEndScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
-        let background = match quick_load_texture("assets/images/backgrounds/Space Background (9).png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-
-        let font = match load_ttf_font("assets/fonts/pixel.ttf").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
+        let background = ctx.assets.results_background;
+        let font = ctx.assets.font;
 
-        let frame = match quick_load_texture("assets/images/ui/frame.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
         let nine_slice_frame = Element {
-            tex: frame,
+            tex: ctx.assets.frame,
             element_type: ElementType::NineSlice(vec2(10.0, 10.0))
         };
 
         let nine_slice_button = Element {
-            tex: match quick_load_texture("assets/images/ui/button.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        },
+            tex: ctx.assets.button,
             element_type: ElementType::NineSlice(vec2(10.0, 10.0))
         };
 
@@ -59,16 +46,16 @@ impl Scene for GameEndScene {
         );
         
         loop {
-            set_camera(&self.window_context.camera);
+            set_camera(&ctx.window_context.camera);
 
-            let mouse_pos = self.window_context.camera.screen_to_world(mouse_position().into());
+            let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
 
             draw_texture(background, 0.0, 0.0, WHITE);
 
-            nine_slice_frame.draw(justify_rect(self.window_context.active_screen_size.x / 2.0, 20.0, self.window_context.active_screen_size.x - 100.0, 300.0, vec2(0.5, 0.0)), WHITE);
+            nine_slice_frame.draw(justify_rect(ctx.window_context.active_screen_size.x / 2.0, 20.0, ctx.window_context.active_screen_size.x - 100.0, 300.0, vec2(0.5, 0.0)), WHITE);
 
             if element_text_template(
-                justify_rect(self.window_context.active_screen_size.x / 4.0, 400.0 - 15.0, 96.0 * 2.0, 26.0 * 2.0, vec2(0.5, 1.0)),
+                justify_rect(ctx.window_context.active_screen_size.x / 4.0, 400.0 - 15.0, 96.0 * 2.0, 26.0 * 2.0, vec2(0.5, 1.0)),
                 button_template, mouse_pos, "Done",
                 TextParams {
                     font,
@@ -77,20 +64,11 @@ impl Scene for GameEndScene {
                     ..Default::default()
                 }
             ).clicked() {
-                let (difficulty, value) = match self.return_to.clone() {
-                    ReturnTo::MainMenu(difficulty, value) => { (Some(difficulty), Some(value)) }
-                    ReturnTo::Editor => { (None, None) }
-                };
-
-                return Some(Box::new(MainMenuScene {
-                    window_context: self.window_context.clone(),
-                    selected_difficulty: difficulty,
-                    selected_song_idx: value
-                }));
+                return SceneChange::Pop(Transition::Fade);
             }
 
             if element_text_template(
-                justify_rect(self.window_context.active_screen_size.x - self.window_context.active_screen_size.x / 4.0, 400.0 - 15.0, 96.0 * 2.0, 26.0 * 2.0, vec2(0.5, 1.0)),
+                justify_rect(ctx.window_context.active_screen_size.x - ctx.window_context.active_screen_size.x / 4.0, 400.0 - 15.0, 96.0 * 2.0, 26.0 * 2.0, vec2(0.5, 1.0)),
                 button_template, mouse_pos, "Retry",
                 TextParams {
                     font,
@@ -99,7 +77,7 @@ impl Scene for GameEndScene {
                     ..Default::default()
                 }
             ).clicked() || is_key_pressed(KeyCode::Space) {
-                return Some(Box::new(NoteGameplayScene::new(self.window_context.clone(), &self.file_path.clone(), self.return_to.clone())));
+                return SceneChange::Replace(Box::new(NoteGameplayScene::new(&self.file_path, self.modifiers)), Transition::Fade);
             }
 
             let status_text = match self.beat_level {
@@ -107,7 +85,7 @@ impl Scene for GameEndScene {
                 false => { "Ship Destroyed" }
             };
 
-            draw_text_justified(status_text, vec2(self.window_context.active_screen_size.x / 2.0, 30.0), TextParams {
+            draw_text_justified(status_text, vec2(ctx.window_context.active_screen_size.x / 2.0, 30.0), TextParams {
                 font,
                 font_size: 100,
                 font_scale: 0.25,
@@ -123,6 +101,20 @@ impl Scene for GameEndScene {
                 ..Default::default()
             }, vec2(0.0, 1.0));
 
+            if !self.modifiers.is_default() {
+                let seed_text = match self.modifiers.lanes {
+                    LaneModifier::Shuffle => format!(", Seed {}", self.modifiers.shuffle_seed),
+                    _ => String::new()
+                };
+                draw_text_justified(&format!("Mods: {} (x{:.2}){}", self.modifiers.tag(), self.modifiers.score_multiplier(), seed_text), vec2(70.0, 80.0), TextParams {
+                    font,
+                    font_size: 40,
+                    font_scale: 0.25,
+                    color: LIGHTGRAY,
+                    ..Default::default()
+                }, vec2(0.0, 1.0));
+            }
+
             draw_text_justified("NOTES", vec2(70.0, 100.0), TextParams {
                 font,
                 font_size: 90,
@@ -131,7 +123,7 @@ impl Scene for GameEndScene {
                 ..Default::default()
             }, vec2(0.0, 1.0));
 
-            draw_text_justified(&format!("Perfect: {}", self.perfect_notes.separate_with_commas()), vec2(70.0, 140.0), TextParams {
+            draw_text_justified(&format!("Perfect: {}", self.judgements.count(Judgement::Perfect).separate_with_commas()), vec2(70.0, 140.0), TextParams {
                 font,
                 font_size: 70,
                 font_scale: 0.25,
@@ -139,7 +131,7 @@ impl Scene for GameEndScene {
                 ..Default::default()
             }, vec2(0.0, 1.0));
 
-            draw_text_justified(&format!("Good: {}", self.good_notes.separate_with_commas()), vec2(70.0, 180.0), TextParams {
+            draw_text_justified(&format!("Good: {}", self.judgements.count(Judgement::Good).separate_with_commas()), vec2(70.0, 180.0), TextParams {
                 font,
                 font_size: 65,
                 font_scale: 0.25,
@@ -147,7 +139,7 @@ impl Scene for GameEndScene {
                 ..Default::default()
             }, vec2(0.0, 1.0));
 
-            draw_text_justified(&format!("Ok: {}", self.ok_notes.separate_with_commas()), vec2(70.0, 220.0), TextParams {
+            draw_text_justified(&format!("Ok: {}", self.judgements.count(Judgement::Ok).separate_with_commas()), vec2(70.0, 220.0), TextParams {
                 font,
                 font_size: 60,
                 font_scale: 0.25,
@@ -155,7 +147,7 @@ impl Scene for GameEndScene {
                 ..Default::default()
             }, vec2(0.0, 1.0));
 
-            draw_text_justified(&format!("Incorrect: {}", self.incorrect_notes.separate_with_commas()), vec2(70.0, 260.0), TextParams {
+            draw_text_justified(&format!("Incorrect: {}", self.judgements.count(Judgement::Incorrect).separate_with_commas()), vec2(70.0, 260.0), TextParams {
                 font,
                 font_size: 55,
                 font_scale: 0.25,
@@ -163,28 +155,176 @@ impl Scene for GameEndScene {
                 ..Default::default()
             }, vec2(0.0, 1.0));
 
-            draw_text_justified(&format!("Missed: {}", self.missed_notes.separate_with_commas()), vec2(70.0, 300.0), TextParams {
+            draw_text_justified(&format!("Missed: {}", self.judgements.count(Judgement::Miss).separate_with_commas()), vec2(70.0, 300.0), TextParams {
                 font,
                 font_size: 50,
                 font_scale: 0.25,
                 color: LIGHTGRAY,
                 ..Default::default()
             }, vec2(0.0, 1.0));
-            
-            if is_key_pressed(KeyCode::Escape) {
-                let (difficulty, idx) = match self.return_to.clone() {
-                    ReturnTo::MainMenu(difficulty, idx) => {(Some(difficulty), Some(idx))}
-                    ReturnTo::Editor => {(None, None)}
+
+            // Accuracy
+            let accuracy = self.judgements.accuracy().unwrap_or(0.0);
+            let grade = self.judgements.grade(self.beat_level);
+
+            draw_text_justified(grade.name(), vec2(ctx.window_context.active_screen_size.x - 80.0, 60.0), TextParams {
+                font,
+                font_size: 250,
+                font_scale: 0.25,
+                color: WHITE,
+                ..Default::default()
+            }, vec2(0.5, 0.5));
+
+            draw_text_justified(self.judgements.lamp(self.beat_level).name(), vec2(ctx.window_context.active_screen_size.x - 80.0, 103.0), TextParams {
+                font,
+                font_size: 40,
+                font_scale: 0.25,
+                color: self.judgements.lamp(self.beat_level).color(),
+                ..Default::default()
+            }, vec2(0.5, 0.0));
+
+            draw_text_justified(&format!("Accuracy: {:.2}%", accuracy * 100.0), vec2(300.0, 60.0), TextParams {
+                font,
+                font_size: 70,
+                font_scale: 0.25,
+                color: LIGHTGRAY,
+                ..Default::default()
+            }, vec2(0.0, 1.0));
+
+            draw_text_justified(&format!("Max Combo: {}", self.judgements.max_combo.separate_with_commas()), vec2(300.0, 80.0), TextParams {
+                font,
+                font_size: 40,
+                font_scale: 0.25,
+                color: LIGHTGRAY,
+                ..Default::default()
+            }, vec2(0.0, 1.0));
+
+            draw_text_justified(&format!("Lasers Taken: {}", self.judgements.lasers_taken.separate_with_commas()), vec2(420.0, 80.0), TextParams {
+                font,
+                font_size: 40,
+                font_scale: 0.25,
+                color: LIGHTGRAY,
+                ..Default::default()
+            }, vec2(0.0, 1.0));
+
+            // Timing Histogram
+            let histogram_rect = Rect::new(300.0, 110.0, 160.0, 90.0);
+            draw_rectangle(histogram_rect.x, histogram_rect.y, histogram_rect.w, histogram_rect.h, Color::new(0.0, 0.0, 0.0, 0.4));
+
+            let histogram = self.judgements.offset_histogram(HISTOGRAM_BINS);
+            let tallest_bin = histogram.iter().copied().max().unwrap_or(0).max(1);
+            let bin_width = histogram_rect.w / HISTOGRAM_BINS as f32;
+            for (bin, count) in histogram.iter().enumerate() {
+                let height = histogram_rect.h * (*count as f32 / tallest_bin as f32);
+                let color = match bin < HISTOGRAM_BINS / 2 {
+                    true => SKYBLUE,
+                    false => ORANGE
                 };
+                draw_rectangle(histogram_rect.x + bin_width * bin as f32, histogram_rect.bottom() - height, bin_width - 1.0, height, color);
+            }
+            draw_line(histogram_rect.center().x, histogram_rect.y, histogram_rect.center().x, histogram_rect.bottom(), 1.0, WHITE);
 
-                return Some(Box::new(MainMenuScene {
-                    window_context: self.window_context.clone(),
-                    selected_difficulty: difficulty,
-                    selected_song_idx: idx
-                }));
+            let average_offset = match self.judgements.average_offset() {
+                Some(offset) => format!("{:+.0}ms", offset * 1000.0),
+                None => "-".to_string()
+            };
+            draw_text_justified(&format!("TIMING (Avg {})", average_offset), vec2(300.0, 105.0), TextParams {
+                font,
+                font_size: 40,
+                font_scale: 0.25,
+                color: WHITE,
+                ..Default::default()
+            }, vec2(0.0, 0.0));
+            draw_text_justified("Early", vec2(histogram_rect.x, histogram_rect.bottom() + 3.0), TextParams {
+                font,
+                font_size: 35,
+                font_scale: 0.25,
+                color: SKYBLUE,
+                ..Default::default()
+            }, vec2(0.0, 1.0));
+            draw_text_justified("Late", vec2(histogram_rect.right(), histogram_rect.bottom() + 3.0), TextParams {
+                font,
+                font_size: 35,
+                font_scale: 0.25,
+                color: ORANGE,
+                ..Default::default()
+            }, vec2(1.0, 1.0));
+
+            // Health Graph
+            let health_rect = Rect::new(480.0, 110.0, 160.0, 90.0);
+            draw_rectangle(health_rect.x, health_rect.y, health_rect.w, health_rect.h, Color::new(0.0, 0.0, 0.0, 0.4));
+
+            draw_text_justified("HEALTH", vec2(health_rect.x, 105.0), TextParams {
+                font,
+                font_size: 40,
+                font_scale: 0.25,
+                color: WHITE,
+                ..Default::default()
+            }, vec2(0.0, 0.0));
+
+            let health_over_time = &self.judgements.health_over_time;
+            let song_time = health_over_time.last().map(|x| x.0).unwrap_or(0.0).max(0.001);
+            let graph_point = |(time, health): (f32, i32)| vec2(
+                health_rect.x + health_rect.w * (time / song_time),
+                health_rect.bottom() - health_rect.h * (health as f32 / self.health_rules.max_health as f32)
+            );
+            for points in health_over_time.windows(2) {
+                let start = graph_point(points[0]);
+                let end = graph_point(points[1]);
+                draw_line(start.x, start.y, end.x, end.y, 2.0, GREEN);
+            }
+
+            draw_text_justified(
+                &format!(
+                    "Health Rules {}: +{} hit, -{} miss, -{} laser",
+                    self.health_rules.name(),
+                    self.health_rules.hit_gain,
+                    self.health_rules.miss_loss,
+                    self.health_rules.laser_loss
+                ),
+                vec2(300.0, 214.0),
+                TextParams {
+                    font,
+                    font_size: 30,
+                    font_scale: 0.25,
+                    color: LIGHTGRAY,
+                    ..Default::default()
+                },
+                vec2(0.0, 1.0)
+            );
+
+            // Lane Accuracy
+            draw_text_justified("LANES", vec2(300.0, 230.0), TextParams {
+                font,
+                font_size: 40,
+                font_scale: 0.25,
+                color: WHITE,
+                ..Default::default()
+            }, vec2(0.0, 1.0));
+
+            for (i, lane) in LANES_TOP_TO_BOTTOM.iter().enumerate() {
+                let lane_accuracy = match self.judgements.lane_accuracy(*lane) {
+                    Some(lane_accuracy) => format!("{:.1}%", lane_accuracy * 100.0),
+                    None => "-".to_string()
+                };
+
+                draw_text_justified(
+                    &format!("{}: {}", lane_name(*lane), lane_accuracy),
+                    vec2(300.0 + 170.0 * (i / 2) as f32, 255.0 + 25.0 * (i % 2) as f32),
+                    TextParams {
+                        font,
+                        font_size: 50,
+                        font_scale: 0.25,
+                        color: LIGHTGRAY,
+                        ..Default::default()
+                    }, vec2(0.0, 1.0));
+            }
+
+            if is_key_pressed(KeyCode::Escape) {
+                return SceneChange::Pop(Transition::Fade);
             }
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
 
             next_frame().await;
         }
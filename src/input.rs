@@ -0,0 +1,342 @@
+use gilrs::{Axis, Button, EventType, Gilrs};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// How far a stick has to be pushed before it counts as pressed
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+const AXES: [Axis; 6] = [Axis::LeftStickX, Axis::LeftStickY, Axis::LeftZ, Axis::RightStickX, Axis::RightStickY, Axis::RightZ];
+
+/// Every key that can be bound and the name it's saved under. A key's position in this list
+/// is the number it was saved as before bindings had names.
+const KEY_NAMES: [(KeyCode, &str); 120] = [
+    (KeyCode::Space, "Space"),
+    (KeyCode::Apostrophe, "Apostrophe"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Key0, "Key0"),
+    (KeyCode::Key1, "Key1"),
+    (KeyCode::Key2, "Key2"),
+    (KeyCode::Key3, "Key3"),
+    (KeyCode::Key4, "Key4"),
+    (KeyCode::Key5, "Key5"),
+    (KeyCode::Key6, "Key6"),
+    (KeyCode::Key7, "Key7"),
+    (KeyCode::Key8, "Key8"),
+    (KeyCode::Key9, "Key9"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Equal, "Equal"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::LeftBracket, "LeftBracket"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::RightBracket, "RightBracket"),
+    (KeyCode::GraveAccent, "GraveAccent"),
+    (KeyCode::World1, "World1"),
+    (KeyCode::World2, "World2"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::CapsLock, "CapsLock"),
+    (KeyCode::ScrollLock, "ScrollLock"),
+    (KeyCode::NumLock, "NumLock"),
+    (KeyCode::PrintScreen, "PrintScreen"),
+    (KeyCode::Pause, "Pause"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::F13, "F13"),
+    (KeyCode::F14, "F14"),
+    (KeyCode::F15, "F15"),
+    (KeyCode::F16, "F16"),
+    (KeyCode::F17, "F17"),
+    (KeyCode::F18, "F18"),
+    (KeyCode::F19, "F19"),
+    (KeyCode::F20, "F20"),
+    (KeyCode::F21, "F21"),
+    (KeyCode::F22, "F22"),
+    (KeyCode::F23, "F23"),
+    (KeyCode::F24, "F24"),
+    (KeyCode::F25, "F25"),
+    (KeyCode::Kp0, "Kp0"),
+    (KeyCode::Kp1, "Kp1"),
+    (KeyCode::Kp2, "Kp2"),
+    (KeyCode::Kp3, "Kp3"),
+    (KeyCode::Kp4, "Kp4"),
+    (KeyCode::Kp5, "Kp5"),
+    (KeyCode::Kp6, "Kp6"),
+    (KeyCode::Kp7, "Kp7"),
+    (KeyCode::Kp8, "Kp8"),
+    (KeyCode::Kp9, "Kp9"),
+    (KeyCode::KpDecimal, "KpDecimal"),
+    (KeyCode::KpDivide, "KpDivide"),
+    (KeyCode::KpMultiply, "KpMultiply"),
+    (KeyCode::KpSubtract, "KpSubtract"),
+    (KeyCode::KpAdd, "KpAdd"),
+    (KeyCode::KpEnter, "KpEnter"),
+    (KeyCode::KpEqual, "KpEqual"),
+    (KeyCode::LeftShift, "LeftShift"),
+    (KeyCode::LeftControl, "LeftControl"),
+    (KeyCode::LeftAlt, "LeftAlt"),
+    (KeyCode::LeftSuper, "LeftSuper"),
+    (KeyCode::RightShift, "RightShift"),
+    (KeyCode::RightControl, "RightControl"),
+    (KeyCode::RightAlt, "RightAlt"),
+    (KeyCode::RightSuper, "RightSuper"),
+    (KeyCode::Menu, "Menu"),
+];
+
+pub fn key_code_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|x| x.1 == name).map(|x| x.0)
+}
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|x| x.0 == key).map(|x| x.1)
+}
+
+/// A key from a config saved before bindings had names
+pub fn legacy_key_code(code: u32) -> Option<KeyCode> {
+    KEY_NAMES.get(code as usize).map(|x| x.0)
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    // A keyboard key by its name, like "Up" or "W"
+    Key(String),
+    Button(Button),
+    // A stick or trigger pushed past the threshold, positive being up or right
+    Axis { axis: Axis, positive: bool }
+}
+
+impl Binding {
+    pub fn key(key: KeyCode) -> Option<Self> {
+        key_name(key).map(|x| Binding::Key(x.to_string()))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(name) => name.clone(),
+            Binding::Button(button) => format!("Pad {:?}", button),
+            Binding::Axis { axis, positive } => format!("Pad {:?}{}", axis, match positive {
+                true => "+",
+                false => "-"
+            })
+        }
+    }
+}
+
+/// The name of an action's first binding, for telling the player what to press
+pub fn primary_binding_name(bindings: &[Binding]) -> String {
+    bindings.first().map(|x| x.name()).unwrap_or_else(|| "Nothing".to_string())
+}
+
+/// Actions missing from a saved config get their default bindings
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Controls {
+    pub up_arrow: Vec<Binding>,
+    pub down_arrow: Vec<Binding>,
+    pub left_arrow: Vec<Binding>,
+    pub right_arrow: Vec<Binding>,
+    pub ship_up: Vec<Binding>,
+    pub ship_down: Vec<Binding>,
+    // Send the ship straight to a lane with the direct lanes ship control scheme
+    pub ship_to_left: Vec<Binding>,
+    pub ship_to_up: Vec<Binding>,
+    pub ship_to_right: Vec<Binding>,
+    pub ship_to_down: Vec<Binding>
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        let key = |name: &str| Binding::Key(name.to_string());
+        Self {
+            up_arrow: vec![key("Up"), Binding::Button(Button::DPadUp), Binding::Button(Button::North)],
+            down_arrow: vec![key("Down"), Binding::Button(Button::DPadDown), Binding::Button(Button::South)],
+            left_arrow: vec![key("Left"), Binding::Button(Button::DPadLeft), Binding::Button(Button::West)],
+            right_arrow: vec![key("Right"), Binding::Button(Button::DPadRight), Binding::Button(Button::East)],
+            ship_up: vec![key("W"), Binding::Axis { axis: Axis::LeftStickY, positive: true }, Binding::Button(Button::LeftTrigger)],
+            ship_down: vec![key("S"), Binding::Axis { axis: Axis::LeftStickY, positive: false }, Binding::Button(Button::RightTrigger)],
+            ship_to_left: vec![key("A"), Binding::Axis { axis: Axis::RightStickX, positive: false }],
+            ship_to_up: vec![key("W"), Binding::Axis { axis: Axis::RightStickY, positive: true }],
+            ship_to_right: vec![key("D"), Binding::Axis { axis: Axis::RightStickX, positive: true }],
+            ship_to_down: vec![key("S"), Binding::Axis { axis: Axis::RightStickY, positive: false }]
+        }
+    }
+}
+
+impl Controls {
+    pub const ACTION_NAMES: [&'static str; 10] = [
+        "Left Arrow", "Up Arrow", "Right Arrow", "Down Arrow", "Ship Up", "Ship Down",
+        "Ship To Left", "Ship To Up", "Ship To Right", "Ship To Down"
+    ];
+
+    /// The bindings of each action in ACTION_NAMES
+    pub fn action(&self, action: usize) -> &Vec<Binding> {
+        match action {
+            0 => &self.left_arrow,
+            1 => &self.up_arrow,
+            2 => &self.right_arrow,
+            3 => &self.down_arrow,
+            4 => &self.ship_up,
+            5 => &self.ship_down,
+            6 => &self.ship_to_left,
+            7 => &self.ship_to_up,
+            8 => &self.ship_to_right,
+            _ => &self.ship_to_down
+        }
+    }
+
+    pub fn action_mut(&mut self, action: usize) -> &mut Vec<Binding> {
+        match action {
+            0 => &mut self.left_arrow,
+            1 => &mut self.up_arrow,
+            2 => &mut self.right_arrow,
+            3 => &mut self.down_arrow,
+            4 => &mut self.ship_up,
+            5 => &mut self.ship_down,
+            6 => &mut self.ship_to_left,
+            7 => &mut self.ship_to_up,
+            8 => &mut self.ship_to_right,
+            _ => &mut self.ship_to_down
+        }
+    }
+}
+
+/// Keyboard and gamepad state for the current frame. Gamepads are only read when update is called,
+/// so every scene that takes input calls it once at the start of each frame.
+pub struct Input {
+    // None when gamepads aren't supported, the keyboard still works
+    gilrs: Option<Gilrs>,
+    // Buttons first pressed this frame
+    pressed_buttons: Vec<Button>,
+    // The furthest each axis is pushed on any gamepad, this frame and last frame
+    axes: Vec<(Axis, f32)>,
+    last_axes: Vec<(Axis, f32)>
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            gilrs: Gilrs::new().ok(),
+            pressed_buttons: vec![],
+            axes: vec![],
+            last_axes: vec![]
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.pressed_buttons.clear();
+        self.last_axes = std::mem::take(&mut self.axes);
+
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            if let EventType::ButtonPressed(button, _) = event.event {
+                self.pressed_buttons.push(button);
+            }
+        }
+
+        self.axes = AXES.iter()
+            .map(|axis| {
+                let value = gilrs.gamepads()
+                    .map(|(_, gamepad)| gamepad.value(*axis))
+                    .fold(0.0, |furthest: f32, x| if x.abs() > furthest.abs() { x } else { furthest });
+                (*axis, value)
+            })
+            .collect();
+    }
+
+    fn axis_pushed(axes: &[(Axis, f32)], axis: Axis, positive: bool) -> bool {
+        let value = axes.iter().find(|x| x.0 == axis).map(|x| x.1).unwrap_or(0.0);
+        match positive {
+            true => value >= AXIS_PRESS_THRESHOLD,
+            false => value <= -AXIS_PRESS_THRESHOLD
+        }
+    }
+
+    fn button_down(&self, button: Button) -> bool {
+        self.gilrs.as_ref().is_some_and(|gilrs| gilrs.gamepads().any(|(_, gamepad)| gamepad.is_pressed(button)))
+    }
+
+    pub fn is_down(&self, bindings: &[Binding]) -> bool {
+        bindings.iter().any(|binding| match binding {
+            Binding::Key(name) => key_code_from_name(name).is_some_and(is_key_down),
+            Binding::Button(button) => self.button_down(*button),
+            Binding::Axis { axis, positive } => Self::axis_pushed(&self.axes, *axis, *positive)
+        })
+    }
+
+    /// Whether any of the bindings went down this frame
+    pub fn is_pressed(&self, bindings: &[Binding]) -> bool {
+        bindings.iter().any(|binding| match binding {
+            Binding::Key(name) => key_code_from_name(name).is_some_and(is_key_pressed),
+            Binding::Button(button) => self.pressed_buttons.contains(button),
+            Binding::Axis { axis, positive } => Self::axis_pushed(&self.axes, *axis, *positive)
+                && !Self::axis_pushed(&self.last_axes, *axis, *positive)
+        })
+    }
+
+    /// The first key, button or axis pressed this frame, for rebinding controls
+    pub fn last_pressed(&self) -> Option<Binding> {
+        if let Some(binding) = get_last_key_pressed().and_then(Binding::key) {
+            return Some(binding);
+        }
+        if let Some(button) = self.pressed_buttons.first() {
+            return Some(Binding::Button(*button));
+        }
+
+        AXES.iter()
+            .flat_map(|axis| [(*axis, true), (*axis, false)])
+            .find(|(axis, positive)| Self::axis_pushed(&self.axes, *axis, *positive) && !Self::axis_pushed(&self.last_axes, *axis, *positive))
+            .map(|(axis, positive)| Binding::Axis { axis, positive })
+    }
+}
@@ -9,16 +9,23 @@ pub static NvOptimusEnablement: i32 = 1;
 pub static AmdPowerXpressRequestHighPerformance: i32 = 1;
 
 use std::env;
-use std::fs::{read_to_string};
+use std::sync::OnceLock;
 use macroquad::miniquad::conf::Icon;
 use macroquad::prelude::*;
 use macroquad_aspect::prelude::*;
 use crate::main_menu_scene::MainMenuScene;
-use crate::scene::Scene;
-use crate::utils::Config;
+use crate::scene::{Scene, SceneStack};
+use crate::config::{Config, load_config};
+use crate::error_scene::ErrorScene;
+use crate::game_context::GameContext;
 
 mod utils;
 mod scene;
+mod transition;
+mod config;
+mod input;
+mod game_context;
+mod audio;
 
 mod note_gameplay_scene;
 mod porpus_scene;
@@ -30,18 +37,26 @@ mod beatmap_editor_scene;
 mod midi_converter;
 
 mod error_scene;
+mod overlays;
 
 mod tutorial_scene;
 
 mod ui;
 
+// Loaded once for both the window and the game, loading writes the config file if it's missing
+static STARTUP_CONFIG: OnceLock<Config> = OnceLock::new();
+
+fn startup_config() -> &'static Config {
+    STARTUP_CONFIG.get_or_init(load_config)
+}
+
 fn window_conf() -> Conf {
     // let mut current_exe = env::current_exe().unwrap();
     // current_exe.pop();
     // env::set_current_dir(&current_exe).unwrap();
     // println!("{:?}", env::current_dir().unwrap());
 
-    let config = serde_json::from_str::<Config>(&read_to_string("assets/config.json").unwrap()).unwrap();
+    let config = startup_config();
 
     Conf {
         window_title: "The Beat Of Space".to_string(),
@@ -61,7 +76,7 @@ fn window_conf() -> Conf {
 #[macroquad::main(window_conf)]
 async fn main() {
 
-    let config = serde_json::from_str::<Config>(&read_to_string("assets/config.json").unwrap()).unwrap();
+    let config = startup_config().clone();
 
     let args: Vec<String> = env::args().collect();
 
@@ -71,11 +86,15 @@ async fn main() {
             let bpm = args[i + 1].clone().parse::<f32>().unwrap();
             let midi_path = args[i + 2].clone();
             let song_path =  args[i + 3].clone();
+            // The music the chart plays to, Goldn if it isn't given
+            let music_path = args.get(i + 4).cloned()
+                .unwrap_or_else(|| "assets/songs/music_files/Goldn.wav".to_string());
 
             let midi_convert = midi_converter::MidiConverter {
                 bpm,
                 midi_path,
-                song_path
+                song_path,
+                music_path
             };
             midi_convert.load_midi().await;
 
@@ -90,24 +109,13 @@ async fn main() {
     window_context.forced = false;
     window_context.scale = config.resolution_scale;
 
-    let mut scene: Box<dyn Scene> = Box::new(MainMenuScene {
-        window_context,
-        selected_difficulty: None,
-        selected_song_idx: None
-    });
-
-    loop {
+    let mut ctx = GameContext::new(window_context, config).await;
 
-        let next_scene = scene.run().await;
+    let first_scene: Box<dyn Scene> = match ctx.assets.missing.is_empty() {
+        true => Box::new(MainMenuScene::default()),
+        false => Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)"))
+    };
 
-        if let Some(next_scene) = next_scene {
-            scene = next_scene;
-        }
-        else {
-            break;
-        }
-
-        next_frame().await;
-    }
+    SceneStack::new(first_scene).run(&mut ctx).await;
 
 }
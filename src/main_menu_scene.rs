@@ -1,26 +1,44 @@
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
-use std::io::Write;
 use async_trait::async_trait;
-use kira::manager::{AudioManager, AudioManagerSettings};
-use kira::manager::backend::cpal::CpalBackend;
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use kira::sound::streaming::StreamingSoundSettings;
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::*;
 use serde::{Deserialize, Serialize};
 use thousands::Separable;
+use crate::audio::{fade_tween, Music, Sfx};
 use crate::beatmap_editor_scene::BeatmapEditorScene;
-use crate::error_scene::ErrorScene;
-
-use crate::note_gameplay_scene::{NoteGameplayScene, ReturnTo};
+use crate::config::{save_config, MAX_RESOLUTION_SCALE};
+use crate::game_context::GameContext;
+use crate::input::{primary_binding_name, Controls};
+
+use crate::note_gameplay_scene::NoteGameplayScene;
+use crate::note_gameplay_scene::practice::PracticeLoop;
+use crate::note_gameplay_scene::constants::{MAX_SCROLL_SPEED, MIN_SCROLL_SPEED, SCROLL_SPEED_STEP};
+use crate::note_gameplay_scene::ghost_taps::GhostTapPolicy;
+use crate::note_gameplay_scene::ship_controls::ShipControlScheme;
+use crate::note_gameplay_scene::judgement_log::{Grade, Lamp};
+use crate::note_gameplay_scene::modifiers::{LaneModifier, Modifiers, RATE_STEP};
 use crate::note_gameplay_scene::song::Song;
+use crate::overlays::ConfirmDialog;
 use crate::porpus_scene::PorpusScene;
-use crate::scene::Scene;
+use crate::scene::{Overlay, Scene, SceneChange};
+use crate::transition::Transition;
 use crate::tutorial_scene::TutorialScene;
 use crate::ui::*;
-use crate::utils::{Config, key_code_to_u32, quick_load_texture, Timer, u32_to_key_code};
+use crate::utils::Timer;
+
+const MENU_MUSIC_PATH: &str = "assets/songs/music_files/ForestLullaby.wav";
+// Seconds the music takes to fade from one track to the next while browsing songs
+const PREVIEW_CROSSFADE_TIME: f32 = 0.75;
+const DEFAULT_PREVIEW_LENGTH: f32 = 15.0;
 
+fn default_preview_length() -> f32 {
+    DEFAULT_PREVIEW_LENGTH
+}
+
+#[derive(Clone, Copy, Default)]
 pub enum MenuState {
+    #[default]
     MainMenu,
     PlayMenu,
     Settings,
@@ -45,7 +63,13 @@ pub struct SongDatabase {
 pub struct SongData {
     pub name: String,
     pub json_name: String,
-    pub difficulties: Vec<String>
+    pub difficulties: Vec<String>,
+    // Seconds into the song its preview starts, the menu music keeps playing for songs without one
+    #[serde(default)]
+    pub preview_start: Option<f32>,
+    // Seconds of the song the preview loops over
+    #[serde(default = "default_preview_length")]
+    pub preview_length: f32
 }
 
 impl Display for Difficulty {
@@ -60,42 +84,33 @@ impl Display for Difficulty {
     }
 }
 
+#[derive(Default)]
 pub struct MainMenuScene {
-    pub window_context: WindowContext,
+    // The menu to open on, so coming back from a song lands where it was picked
+    pub menu_state: MenuState,
     pub selected_difficulty: Option<Difficulty>,
-    pub selected_song_idx: Option<usize>
+    pub selected_song_idx: Option<usize>,
+    pub modifiers: Modifiers
 }
 
 #[async_trait]
 impl Scene for MainMenuScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
 
-        let mut state = MenuState::MainMenu;
+        let mut state = self.menu_state;
 
-        let background = match quick_load_texture("assets/images/backgrounds/Space Background (15).png").await  {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let background = ctx.assets.menu_background;
 
-        let font = match load_ttf_font("assets/fonts/pixel.ttf").await  {
-            Ok(font) => font,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let font = ctx.assets.font;
 
-        let frame = match quick_load_texture("assets/images/ui/frame.png").await {
-            Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let frame = ctx.assets.frame;
         let nine_slice_frame = Element {
             tex: frame,
             element_type: ElementType::NineSlice(vec2(10.0, 10.0))
         };
 
         let nine_slice_button = Element {
-            tex: match quick_load_texture("assets/images/ui/button.png").await  {
-                Ok(tex) => tex,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            },
+            tex: ctx.assets.button,
             element_type: ElementType::NineSlice(vec2(10.0, 10.0))
         };
 
@@ -113,10 +128,7 @@ impl Scene for MainMenuScene {
 
         let plus_template = UITemplate::new(
             Element {
-                tex: match quick_load_texture("assets/images/ui/plus.png").await  {
-                    Ok(tex) => tex,
-                    Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-                },
+                tex: ctx.assets.plus,
                 element_type: ElementType::Texture
             },
             Color::new(1.0, 1.0, 1.0, 1.0),
@@ -125,27 +137,18 @@ impl Scene for MainMenuScene {
 
         let minus_template = UITemplate::new(
             Element {
-                tex: match quick_load_texture("assets/images/ui/minus.png").await {
-                Ok(tex) => tex,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            },
+                tex: ctx.assets.minus,
                 element_type: ElementType::Texture
             },
             Color::new(1.0, 1.0, 1.0, 1.0),
             Some(Color::new(0.8, 0.8, 0.8, 1.0))
         );
 
-        let mut sound_manager = AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-        let sound = match StaticSoundData::from_file(
-            "assets/songs/music_files/ForestLullaby.wav",
-            StaticSoundSettings::default(),
-        ) {
-            Ok(song) => song,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+        let mut music = match ctx.audio.play_music(MENU_MUSIC_PATH) {
+            Ok(music) => music,
+            Err(err) => return SceneChange::error(&err.to_string())
         };
 
-        let mut music = sound_manager.play(sound).unwrap();
-
         let mut load_scene_timer = Timer::new(3.5, false);
         let mut load_watch_timer = Timer::new(3.5, false);
 
@@ -156,42 +159,54 @@ impl Scene for MainMenuScene {
 
         let song_database = match serde_json::from_str::<SongDatabase>(&match load_string("assets/song_data.json").await {
             Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("song_data missing, please fix or reinstall", self.window_context.clone())))
+            Err(_) => return SceneChange::error("song_data missing, please fix or reinstall")
         }) {
             Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("song_data format is wrong", self.window_context.clone())))
+            Err(_) => return SceneChange::error("song_data format is wrong")
         };
         let mut chosen_song_idx = self.selected_song_idx.unwrap_or(0);
 
         let mut song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
             Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
         }) {
             Ok(tex) => tex,
-            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Song Format Incorrect")
         };
-        let mut config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
 
-        music.set_volume(config.volume, Default::default()).unwrap();
+        // Song the music was last picked for, None when no song is being browsed
+        let mut browsed_song_idx: Option<usize> = None;
+        // Whether a song preview is playing instead of the menu music
+        let mut previewing = false;
 
         let mut changing_song = false;
+        let mut changing_mods = false;
+        let mut changing_practice = false;
+        let mut practicing = false;
+        // Section picked in the practice panel, reset to the whole song each time it's opened
+        let mut practice_range = PracticeLoop::whole_song(song.song_length * song.bpm / 60.0);
+
+        // Best grade and lamp of each chart shown in the song list, keyed by chart path
+        let mut chart_records: HashMap<String, (Option<Grade>, Lamp)> = HashMap::new();
 
         let mut play_button_pos = 0.0;
         let mut settings_button_pos = 0.0;
         let mut quit_button_pos = 0.0;
 
-        let start_fullscreen = config.fullscreen;
+        let start_fullscreen = ctx.config.fullscreen;
 
         let mut checking_input = 0;
 
         let mut fps_display = false;
 
         loop {
-            set_camera(&self.window_context.camera);
+            ctx.input.update();
+
+            set_camera(&ctx.window_context.camera);
 
             draw_texture_ex(background, 0.0, 0.0, WHITE, Default::default());
 
-            let mouse_pos = self.window_context.camera.screen_to_world(mouse_position().into());
+            let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
 
             if is_key_pressed(KeyCode::F3) {
                 fps_display = !fps_display;
@@ -200,7 +215,7 @@ impl Scene for MainMenuScene {
             if fps_display {
                 draw_text_justified(
                     format!("{}", get_fps()).as_str(),
-                    vec2(self.window_context.active_screen_size.x - 5.0, 5.0),
+                    vec2(ctx.window_context.active_screen_size.x - 5.0, 5.0),
                     TextParams {
                         font,
                         font_size: 40,
@@ -214,8 +229,8 @@ impl Scene for MainMenuScene {
 
             match state {
                 MenuState::MainMenu => {
-                    if is_key_pressed(KeyCode::Escape) {
-                        return None;
+                    if is_key_pressed(KeyCode::Escape) && ConfirmDialog::new("Quit the game?").run(ctx).await {
+                        return SceneChange::Quit;
                     }
                     let mut play_rect = justify_rect(50.0, 50.0, 96.0 * 1.5, 26.0 * 1.25, vec2(0.0, 0.5));
                     if hover_rect(play_rect, mouse_pos) {
@@ -279,20 +294,20 @@ impl Scene for MainMenuScene {
                             font_scale: 0.25,
                             ..Default::default()
                         }
-                    ).clicked() {
-                        return None
+                    ).clicked() && ConfirmDialog::new("Quit the game?").run(ctx).await {
+                        return SceneChange::Quit
                     }
                 }
                 MenuState::PlayMenu => {
                     // Difficulty Selection Menu
                     nine_slice_frame.draw(
-                        justify_rect(50.0, 50.0, self.window_context.active_screen_size.x / 4.0, 96.0 * 2.5, vec2(0.0, 0.0)),
+                        justify_rect(50.0, 50.0, ctx.window_context.active_screen_size.x / 4.0, 96.0 * 2.5, vec2(0.0, 0.0)),
                         WHITE
                     );
 
                     // Easy Button
                     if element_text_template(
-                        justify_rect(50.0 + self.window_context.active_screen_size.x / 8.0, 75.0, self.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
+                        justify_rect(50.0 + ctx.window_context.active_screen_size.x / 8.0, 75.0, ctx.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
                         match active_difficulty {
                             Difficulty::Easy => { button_template }
                             _ => { faint_button_template }
@@ -317,16 +332,16 @@ impl Scene for MainMenuScene {
                         }
                         song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
                         }) {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Song Format Incorrect")
                         };
                     }
 
                     // Medium Button
                     if element_text_template(
-                        justify_rect(50.0 + self.window_context.active_screen_size.x / 8.0, 75.0 + 40.0, self.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
+                        justify_rect(50.0 + ctx.window_context.active_screen_size.x / 8.0, 75.0 + 40.0, ctx.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
                         match active_difficulty {
                             Difficulty::Medium => { button_template }
                             _ => { faint_button_template }
@@ -351,16 +366,16 @@ impl Scene for MainMenuScene {
                         }
                         song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
                         }) {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Song Format Incorrect")
                         };
                     }
 
                     // Hard Button
                     if element_text_template(
-                        justify_rect(50.0 + self.window_context.active_screen_size.x / 8.0, 75.0 + 80.0, self.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
+                        justify_rect(50.0 + ctx.window_context.active_screen_size.x / 8.0, 75.0 + 80.0, ctx.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
                         match active_difficulty {
                             Difficulty::Hard => { button_template }
                             _ => { faint_button_template }
@@ -385,16 +400,16 @@ impl Scene for MainMenuScene {
                         }
                         song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
                         }) {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Song Format Incorrect")
                         };
                     }
 
                     // Expert Button
                     if element_text_template(
-                        justify_rect(50.0 + self.window_context.active_screen_size.x / 8.0, 75.0 + 120.0, self.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
+                        justify_rect(50.0 + ctx.window_context.active_screen_size.x / 8.0, 75.0 + 120.0, ctx.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
                         match active_difficulty {
                             Difficulty::Expert => { button_template }
                             _ => { faint_button_template }
@@ -419,16 +434,16 @@ impl Scene for MainMenuScene {
                         }
                         song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
                         }) {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Song Format Incorrect")
                         };
                     }
 
                     // Extreme Button
                     if element_text_template(
-                        justify_rect(50.0 + self.window_context.active_screen_size.x / 8.0, 75.0 + 160.0, self.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
+                        justify_rect(50.0 + ctx.window_context.active_screen_size.x / 8.0, 75.0 + 160.0, ctx.window_context.active_screen_size.x / 6.0, 30.0, vec2(0.5, 0.0)),
                         match active_difficulty {
                             Difficulty::Extreme => { button_template }
                             _ => { faint_button_template }
@@ -453,17 +468,217 @@ impl Scene for MainMenuScene {
                         }
                         song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
                         }) {
                             Ok(tex) => tex,
-                            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+                            Err(_) => return SceneChange::error("Song Format Incorrect")
                         };
                     }
 
-                    let song_data_left = self.window_context.active_screen_size.x - 50.0 - self.window_context.active_screen_size.x * 0.56;
-                    let song_data_center = self.window_context.active_screen_size.x - 50.0 - (self.window_context.active_screen_size.x * 0.56) / 2.0;
+                    let song_data_left = ctx.window_context.active_screen_size.x - 50.0 - ctx.window_context.active_screen_size.x * 0.56;
+                    let song_data_center = ctx.window_context.active_screen_size.x - 50.0 - (ctx.window_context.active_screen_size.x * 0.56) / 2.0;
+
+                    if changing_mods {
+                        if is_key_pressed(KeyCode::M) || is_key_pressed(KeyCode::Escape) {
+                            changing_mods = false;
+                        }
+
+                        // Modifiers Panel
+                        nine_slice_frame.draw(justify_rect(ctx.window_context.active_screen_size.x - 50.0, 50.0, ctx.window_context.active_screen_size.x * 0.56, 240.0, vec2(1.0, 0.0)), WHITE);
+
+                        draw_text_justified(
+                            "Modifiers",
+                            vec2(song_data_center, 70.0),
+                            TextParams {
+                                font,
+                                font_size: 80,
+                                font_scale: 0.25,
+                                ..Default::default()
+                            },vec2(0.5, 1.0));
+
+                        draw_text_justified(
+                            &format!("Rate: {:.1}x", self.modifiers.rate),
+                            vec2(song_data_left + 25.0, 110.0),
+                            TextParams {
+                                font,
+                                font_size: 45,
+                                font_scale: 0.25,
+                                ..Default::default()
+                            },vec2(0.0, 0.5));
+
+                        if element_template(justify_rect(song_data_left + 110.0, 110.0, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
+                            self.modifiers.change_rate(-RATE_STEP);
+                        }
+                        if element_template(justify_rect(song_data_left + 135.0, 110.0, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
+                            self.modifiers.change_rate(RATE_STEP);
+                        }
+
+                        let left_column = song_data_center - ctx.window_context.active_screen_size.x * 0.13;
+                        let right_column = song_data_center + ctx.window_context.active_screen_size.x * 0.13;
+                        let toggle_width = ctx.window_context.active_screen_size.x * 0.24;
+                        let on_off = |value: bool| match value {
+                            true => "On",
+                            false => "Off"
+                        };
+                        let toggle_template = |value: bool| match value {
+                            true => button_template,
+                            false => faint_button_template
+                        };
+                        let toggle_text = TextParams {
+                            font,
+                            font_size: 40,
+                            font_scale: 0.25,
+                            ..Default::default()
+                        };
+
+                        if element_text_template(
+                            justify_rect(left_column, 140.0, toggle_width, 26.0, vec2(0.5, 0.5)),
+                            toggle_template(self.modifiers.lanes != LaneModifier::Normal), mouse_pos,
+                            &format!("Lanes: {}", self.modifiers.lanes.name()), toggle_text
+                        ).clicked() {
+                            self.modifiers.lanes = self.modifiers.lanes.next();
+                        }
+                        if element_text_template(
+                            justify_rect(left_column, 175.0, toggle_width, 26.0, vec2(0.5, 0.5)),
+                            toggle_template(self.modifiers.no_fail), mouse_pos,
+                            &format!("No Fail: {}", on_off(self.modifiers.no_fail)), toggle_text
+                        ).clicked() {
+                            self.modifiers.no_fail = !self.modifiers.no_fail;
+                            // No Fail can't be on with the mods that fail a run early
+                            if self.modifiers.no_fail {
+                                self.modifiers.sudden_death = false;
+                                self.modifiers.perfect_only = false;
+                            }
+                        }
+                        if element_text_template(
+                            justify_rect(left_column, 210.0, toggle_width, 26.0, vec2(0.5, 0.5)),
+                            toggle_template(self.modifiers.sudden_death), mouse_pos,
+                            &format!("Sudden Death: {}", on_off(self.modifiers.sudden_death)), toggle_text
+                        ).clicked() {
+                            self.modifiers.sudden_death = !self.modifiers.sudden_death;
+                            if self.modifiers.sudden_death {
+                                self.modifiers.no_fail = false;
+                            }
+                        }
+                        if element_text_template(
+                            justify_rect(right_column, 140.0, toggle_width, 26.0, vec2(0.5, 0.5)),
+                            toggle_template(self.modifiers.perfect_only), mouse_pos,
+                            &format!("Perfect Only: {}", on_off(self.modifiers.perfect_only)), toggle_text
+                        ).clicked() {
+                            self.modifiers.perfect_only = !self.modifiers.perfect_only;
+                            if self.modifiers.perfect_only {
+                                self.modifiers.no_fail = false;
+                            }
+                        }
+                        if element_text_template(
+                            justify_rect(right_column, 175.0, toggle_width, 26.0, vec2(0.5, 0.5)),
+                            toggle_template(self.modifiers.no_lasers), mouse_pos,
+                            &format!("No Lasers: {}", on_off(self.modifiers.no_lasers)), toggle_text
+                        ).clicked() {
+                            self.modifiers.no_lasers = !self.modifiers.no_lasers;
+                        }
+                        if element_text_template(
+                            justify_rect(right_column, 210.0, toggle_width, 26.0, vec2(0.5, 0.5)),
+                            faint_button_template, mouse_pos,
+                            "Reset", toggle_text
+                        ).clicked() {
+                            self.modifiers = Modifiers::default();
+                        }
+
+                        draw_text_justified(
+                            &format!("Score Multiplier: x{:.2}", self.modifiers.score_multiplier()),
+                            vec2(song_data_center, 255.0),
+                            TextParams {
+                                font,
+                                font_size: 45,
+                                font_scale: 0.25,
+                                ..Default::default()
+                            },vec2(0.5, 0.5));
+                    } else if changing_practice {
+                        if is_key_pressed(KeyCode::P) || is_key_pressed(KeyCode::Escape) {
+                            changing_practice = false;
+                        }
+
+                        // Practice Panel
+                        nine_slice_frame.draw(justify_rect(ctx.window_context.active_screen_size.x - 50.0, 50.0, ctx.window_context.active_screen_size.x * 0.56, 240.0, vec2(1.0, 0.0)), WHITE);
+
+                        draw_text_justified(
+                            "Practice",
+                            vec2(song_data_center, 70.0),
+                            TextParams {
+                                font,
+                                font_size: 80,
+                                font_scale: 0.25,
+                                ..Default::default()
+                            },vec2(0.5, 1.0));
+
+                        let song_beats = song.song_length * song.bpm / 60.0;
+                        let beat_time = |beat: f32| {
+                            let seconds = (beat * 60.0 / song.bpm) as u32;
+                            format!("{}:{:02}", seconds / 60, seconds % 60)
+                        };
+
+                        for (y, label, beat) in [(110.0, "Start", practice_range.start_beat), (140.0, "End", practice_range.end_beat)] {
+                            draw_text_justified(
+                                &format!("{}: Beat {} ({})", label, beat.round(), beat_time(beat)),
+                                vec2(song_data_left + 25.0, y),
+                                TextParams {
+                                    font,
+                                    font_size: 45,
+                                    font_scale: 0.25,
+                                    ..Default::default()
+                                },vec2(0.0, 0.5));
+
+                            let measures = if element_template(justify_rect(song_data_left + 170.0, y, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
+                                -1.0
+                            } else if element_template(justify_rect(song_data_left + 195.0, y, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
+                                1.0
+                            } else {
+                                0.0
+                            };
+                            if measures != 0.0 {
+                                match label {
+                                    "Start" => practice_range.move_start(measures),
+                                    _ => practice_range.move_end(measures, song_beats)
+                                }
+                            }
+                        }
 
-                    if !changing_song {
+                        let button_text = TextParams {
+                            font,
+                            font_size: 40,
+                            font_scale: 0.25,
+                            ..Default::default()
+                        };
+                        let button_width = ctx.window_context.active_screen_size.x * 0.24;
+
+                        if element_text_template(
+                            justify_rect(song_data_center - ctx.window_context.active_screen_size.x * 0.13, 200.0, button_width, 26.0, vec2(0.5, 0.5)),
+                            faint_button_template, mouse_pos,
+                            "Whole Song", button_text
+                        ).clicked() {
+                            practice_range = PracticeLoop::whole_song(song_beats);
+                        }
+                        if element_text_template(
+                            justify_rect(song_data_center + ctx.window_context.active_screen_size.x * 0.13, 200.0, button_width, 26.0, vec2(0.5, 0.5)),
+                            button_template, mouse_pos,
+                            "Start", button_text
+                        ).clicked() || is_key_pressed(KeyCode::Enter) {
+                            state = MenuState::Loading;
+                            practicing = true;
+                            load_scene_timer.start();
+                        }
+
+                        draw_text_justified(
+                            "Loops the section, [ and ] move it while playing",
+                            vec2(song_data_center, 255.0),
+                            TextParams {
+                                font,
+                                font_size: 35,
+                                font_scale: 0.25,
+                                ..Default::default()
+                            },vec2(0.5, 0.5));
+                    } else if !changing_song {
                         if is_key_pressed(KeyCode::Down) {
                             match active_difficulty {
                                 Difficulty::Easy => {active_difficulty = Difficulty::Medium}
@@ -486,11 +701,18 @@ impl Scene for MainMenuScene {
                         if is_key_pressed(KeyCode::S) {
                             changing_song = true;
                         }
+                        if is_key_pressed(KeyCode::M) {
+                            changing_mods = true;
+                        }
+                        if is_key_pressed(KeyCode::P) {
+                            changing_practice = true;
+                            practice_range = PracticeLoop::whole_song(song.song_length * song.bpm / 60.0);
+                        }
                         if is_key_pressed(KeyCode::Escape) {
                             state = MenuState::MainMenu;
                         }
                         // Song Data Panel
-                        nine_slice_frame.draw(justify_rect(self.window_context.active_screen_size.x - 50.0, 50.0, self.window_context.active_screen_size.x * 0.56, 240.0, vec2(1.0, 0.0)), WHITE);
+                        nine_slice_frame.draw(justify_rect(ctx.window_context.active_screen_size.x - 50.0, 50.0, ctx.window_context.active_screen_size.x * 0.56, 240.0, vec2(1.0, 0.0)), WHITE);
 
                         draw_text_justified(
                             song_database.songs[chosen_song_idx].name.as_str(),
@@ -502,8 +724,12 @@ impl Scene for MainMenuScene {
                                 ..Default::default()
                             },vec2(0.5, 1.0));
 
+                        let high_score_text = match self.modifiers.is_default() {
+                            true => format!("High Score: {}", song.high_score.separate_with_commas()),
+                            false => format!("High Score ({}): {}", self.modifiers.tag(), song.modded_high_scores.get(&self.modifiers.tag()).unwrap_or(&0).separate_with_commas())
+                        };
                         draw_text_justified(
-                            &format!("High Score: {}", song.high_score.separate_with_commas()),
+                            &high_score_text,
                             vec2(song_data_left + 25.0, 175.0),
                             TextParams {
                                 font,
@@ -512,6 +738,21 @@ impl Scene for MainMenuScene {
                                 ..Default::default()
                             },vec2(0.0, 1.0));
 
+                        let best_text = match song.best_grade {
+                            Some(best_grade) => format!("Best: {} ({})", best_grade.name(), song.lamp.name()),
+                            None => format!("Best: {}", song.lamp.name())
+                        };
+                        draw_text_justified(
+                            &best_text,
+                            vec2(song_data_left + 25.0, 150.0),
+                            TextParams {
+                                font,
+                                font_size: 40,
+                                font_scale: 0.25,
+                                color: song.lamp.color(),
+                                ..Default::default()
+                            },vec2(0.0, 1.0));
+
                         draw_text_justified(
                             &format!("Length: {} Seconds", song.song_length),
                             vec2(song_data_left + 25.0, 125.0),
@@ -524,7 +765,7 @@ impl Scene for MainMenuScene {
 
                         // Change Song Button
                         if element_text_template(
-                            justify_rect(song_data_center + 100.0, self.window_context.active_screen_size.y - 60.0, 96.0 * 1.5, 26.0 * 1.5, vec2(0.5, 1.0)),
+                            justify_rect(song_data_center + 100.0, ctx.window_context.active_screen_size.y - 60.0, 96.0 * 1.5, 26.0 * 1.5, vec2(0.5, 1.0)),
                             button_template,
                             mouse_pos,
                             "Songs",
@@ -536,6 +777,7 @@ impl Scene for MainMenuScene {
                             }
                         ).clicked() {
                             changing_song = true;
+                            changing_mods = false;
                         }
 
                     } else {
@@ -557,13 +799,24 @@ impl Scene for MainMenuScene {
                             changing_song = false;
                         }
                         // Song Choice Panel
-                        nine_slice_frame.draw(justify_rect(song_data_center - 100.0, 50.0, self.window_context.active_screen_size.x * 0.28, 240.0, vec2(0.5, 0.0)), WHITE);
+                        nine_slice_frame.draw(justify_rect(song_data_center - 100.0, 50.0, ctx.window_context.active_screen_size.x * 0.28, 240.0, vec2(0.5, 0.0)), WHITE);
 
                         let mut total_songs = 0;
                         for song_idx in 0..song_database.songs.len() {
                             if song_database.songs[song_idx].difficulties.contains(&active_difficulty.to_string()) {
+                                let button_rect = justify_rect(song_data_center - 100.0, 75.0 + (50.0 * total_songs as f32), ctx.window_context.active_screen_size.x * 0.22, 26.0 * 1.5, vec2(0.5, 0.0));
+
+                                let chart_path = format!("assets/songs/{}/{}", active_difficulty, song_database.songs[song_idx].json_name);
+                                if !chart_records.contains_key(&chart_path) {
+                                    let record = match load_string(&chart_path).await.ok().and_then(|x| serde_json::from_str::<Song>(&x).ok()) {
+                                        Some(chart) => (chart.best_grade, chart.lamp),
+                                        None => (None, Lamp::NotPlayed)
+                                    };
+                                    chart_records.insert(chart_path.clone(), record);
+                                }
+
                                 if element_text_template(
-                                    justify_rect(song_data_center - 100.0, 75.0 + (50.0 * total_songs as f32), self.window_context.active_screen_size.x * 0.22, 26.0 * 1.5, vec2(0.5, 0.0)),
+                                    button_rect,
                                     {
                                         if song_idx == chosen_song_idx {
                                             button_template
@@ -584,13 +837,32 @@ impl Scene for MainMenuScene {
                                     changing_song = false;
                                     song = match serde_json::from_str::<Song>(&match load_string(&format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name)).await {
                                         Ok(tex) => tex,
-                                        Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+                                        Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
                                     }) {
                                         Ok(tex) => tex,
-                                        Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+                                        Err(_) => return SceneChange::error("Song Format Incorrect")
                                     };
                                 }
 
+                                // Record Badges
+                                let (best_grade, lamp) = chart_records[&chart_path];
+                                draw_text_justified(lamp.badge(), vec2(button_rect.x + 6.0, button_rect.center().y), TextParams {
+                                    font,
+                                    font_size: 35,
+                                    font_scale: 0.25,
+                                    color: lamp.color(),
+                                    ..Default::default()
+                                }, vec2(0.0, 0.5));
+                                if let Some(best_grade) = best_grade {
+                                    draw_text_justified(best_grade.name(), vec2(button_rect.right() - 6.0, button_rect.center().y), TextParams {
+                                        font,
+                                        font_size: 50,
+                                        font_scale: 0.25,
+                                        color: lamp.color(),
+                                        ..Default::default()
+                                    }, vec2(1.0, 0.5));
+                                }
+
                                 total_songs += 1;
                             }
                         }
@@ -598,7 +870,7 @@ impl Scene for MainMenuScene {
 
                     // Play Button
                     if element_text_template(
-                        justify_rect(song_data_center - 100.0, self.window_context.active_screen_size.y - 60.0, 96.0 * 1.5, 26.0 * 1.5, vec2(0.5, 1.0)),
+                        justify_rect(song_data_center - 100.0, ctx.window_context.active_screen_size.y - 60.0, 96.0 * 1.5, 26.0 * 1.5, vec2(0.5, 1.0)),
                         button_template,
                         mouse_pos,
                         "Play",
@@ -613,9 +885,49 @@ impl Scene for MainMenuScene {
                         load_scene_timer.start();
                     }
 
+                    // Practice Button
+                    if element_text_template(
+                        justify_rect(50.0, ctx.window_context.active_screen_size.y - 25.0, 96.0 * 1.3, 26.0 * 1.3, vec2(0.0, 1.0)),
+                        button_template,
+                        mouse_pos,
+                        "Practice",
+                        TextParams {
+                            font,
+                            font_size: 60,
+                            font_scale: 0.25,
+                            ..Default::default()
+                        }
+                    ).clicked() {
+                        changing_practice = !changing_practice;
+                        changing_mods = false;
+                        changing_song = false;
+                        practice_range = PracticeLoop::whole_song(song.song_length * song.bpm / 60.0);
+                    }
+
+                    // Modifiers Button
+                    if element_text_template(
+                        justify_rect(song_data_center + 100.0, ctx.window_context.active_screen_size.y - 20.0, 96.0 * 1.5, 26.0 * 1.25, vec2(0.5, 1.0)),
+                        match self.modifiers.is_default() {
+                            true => faint_button_template,
+                            false => button_template
+                        },
+                        mouse_pos,
+                        "Mods",
+                        TextParams {
+                            font,
+                            font_size: 60,
+                            font_scale: 0.25,
+                            ..Default::default()
+                        }
+                    ).clicked() {
+                        changing_mods = !changing_mods;
+                        changing_song = false;
+                        changing_practice = false;
+                    }
+
                     // Watch Button
                     if element_text_template(
-                        justify_rect(song_data_center - 100.0, self.window_context.active_screen_size.y - 20.0, 96.0 * 1.5, 26.0 * 1.25, vec2(0.5, 1.0)),
+                        justify_rect(song_data_center - 100.0, ctx.window_context.active_screen_size.y - 20.0, 96.0 * 1.5, 26.0 * 1.25, vec2(0.5, 1.0)),
                         button_template,
                         mouse_pos,
                         "Watch",
@@ -632,7 +944,7 @@ impl Scene for MainMenuScene {
 
                     // Back Button
                     if element_text_template(
-                        justify_rect(50.0, self.window_context.active_screen_size.y - 70.0, 96.0 * 1.3, 26.0 * 1.3, vec2(0.0, 1.0)),
+                        justify_rect(50.0, ctx.window_context.active_screen_size.y - 70.0, 96.0 * 1.3, 26.0 * 1.3, vec2(0.0, 1.0)),
                         button_template,
                         mouse_pos,
                         "Back",
@@ -645,6 +957,10 @@ impl Scene for MainMenuScene {
                     ).clicked() {
                         if changing_song {
                             changing_song = false;
+                        } else if changing_mods {
+                            changing_mods = false;
+                        } else if changing_practice {
+                            changing_practice = false;
                         } else {
                             state = MenuState::MainMenu
                         }
@@ -657,7 +973,7 @@ impl Scene for MainMenuScene {
                     }
 
                     if element_text_template(
-                        justify_rect(50.0, self.window_context.active_screen_size.y - 15.0, 96.0 * 1.35, 26.0 * 1.1, vec2(0.0, 1.0)),
+                        justify_rect(50.0, ctx.window_context.active_screen_size.y - 15.0, 96.0 * 1.35, 26.0 * 1.1, vec2(0.0, 1.0)),
                         button_template, mouse_pos, "Back",
                         TextParams {
                             font,
@@ -672,7 +988,7 @@ impl Scene for MainMenuScene {
 
                     nine_slice_frame.draw(justify_rect(40.0, 50.0, 200.0, 40.0, vec2(0.0, 0.5)), WHITE);
 
-                    draw_text_justified("Volume: ", vec2(50.0, 50.0), TextParams {
+                    draw_text_justified("Music: ", vec2(50.0, 50.0), TextParams {
                         font,
                         font_size: 45,
                         font_scale: 0.25,
@@ -680,17 +996,15 @@ impl Scene for MainMenuScene {
                     }, vec2(0.0, 0.5));
 
                     if element_template(justify_rect(125.0, 50.0, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
-                        config.volume -= 0.05;
-                        config.volume = clamp(config.volume, 0.0, 1.0);
+                        ctx.config.music_volume -= 0.05;
+                        ctx.config.music_volume = clamp(ctx.config.music_volume, 0.0, 1.0);
 
-                        let mut data = File::create("assets/config.json").unwrap();
-                        data.write_all((serde_json::to_string_pretty(&config).unwrap()).as_ref()).unwrap();
-                        config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
+                        ctx.config = save_config(&ctx.config);
 
-                        music.set_volume(config.volume, Default::default()).unwrap();
+                        ctx.audio.set_volumes(&ctx.config);
                     }
 
-                    draw_text_justified(&format!("{}%", (config.volume * 100.0).round()), vec2(150.0, 50.0), TextParams {
+                    draw_text_justified(&format!("{}%", (ctx.config.music_volume * 100.0).round()), vec2(150.0, 50.0), TextParams {
                         font,
                         font_size: 45,
                         font_scale: 0.25,
@@ -698,21 +1012,57 @@ impl Scene for MainMenuScene {
                     }, vec2(0.0, 0.5));
 
                     if element_template(justify_rect(195.0, 50.0, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
-                        config.volume += 0.05;
-                        config.volume = clamp(config.volume, 0.0, 1.0);
+                        ctx.config.music_volume += 0.05;
+                        ctx.config.music_volume = clamp(ctx.config.music_volume, 0.0, 1.0);
+
+                        ctx.config = save_config(&ctx.config);
+
+                        ctx.audio.set_volumes(&ctx.config);
+                    }
+
+                    nine_slice_frame.draw(justify_rect(40.0, 95.0, 200.0, 40.0, vec2(0.0, 0.5)), WHITE);
+
+                    draw_text_justified("SFX: ", vec2(50.0, 95.0), TextParams {
+                        font,
+                        font_size: 45,
+                        font_scale: 0.25,
+                        ..Default::default()
+                    }, vec2(0.0, 0.5));
+
+                    if element_template(justify_rect(125.0, 95.0, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
+                        ctx.config.sfx_volume -= 0.05;
+                        ctx.config.sfx_volume = clamp(ctx.config.sfx_volume, 0.0, 1.0);
 
-                        let mut data = File::create("assets/config.json").unwrap();
-                        data.write_all((serde_json::to_string_pretty(&config).unwrap()).as_ref()).unwrap();
-                        config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
+                        ctx.config = save_config(&ctx.config);
 
-                        music.set_volume(config.volume, Default::default()).unwrap();
+                        ctx.audio.set_volumes(&ctx.config);
+                        // So the new volume can be heard
+                        ctx.play_sfx(Sfx::UIClick);
+                    }
+
+                    draw_text_justified(&format!("{}%", (ctx.config.sfx_volume * 100.0).round()), vec2(150.0, 95.0), TextParams {
+                        font,
+                        font_size: 45,
+                        font_scale: 0.25,
+                        ..Default::default()
+                    }, vec2(0.0, 0.5));
+
+                    if element_template(justify_rect(195.0, 95.0, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
+                        ctx.config.sfx_volume += 0.05;
+                        ctx.config.sfx_volume = clamp(ctx.config.sfx_volume, 0.0, 1.0);
+
+                        ctx.config = save_config(&ctx.config);
+
+                        ctx.audio.set_volumes(&ctx.config);
+                        // So the new volume can be heard
+                        ctx.play_sfx(Sfx::UIClick);
                     }
 
                     if element_text_template(
-                        justify_rect(self.window_context.active_screen_size.x - 50.0, 100.0, 96.0 * 2.0, 18.0 * 1.8, vec2(1.0, 0.5)),
+                        justify_rect(ctx.window_context.active_screen_size.x - 50.0, 100.0, 96.0 * 2.0, 18.0 * 1.8, vec2(1.0, 0.5)),
                         button_template,
                         mouse_pos,
-                        &format!("Fullscreen: {}", match config.fullscreen {
+                        &format!("Fullscreen: {}", match ctx.config.fullscreen {
                             true => { "On" }
                             false => { "Off" }
                         }),
@@ -723,17 +1073,15 @@ impl Scene for MainMenuScene {
                             ..Default::default()
                         }
                     ).clicked() {
-                        config.fullscreen = !config.fullscreen;
+                        ctx.config.fullscreen = !ctx.config.fullscreen;
 
-                        let mut data = File::create("assets/config.json").unwrap();
-                        data.write_all((serde_json::to_string_pretty(&config).unwrap()).as_ref()).unwrap();
-                        config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
+                        ctx.config = save_config(&ctx.config);
                     }
 
-                    if config.fullscreen != start_fullscreen {
+                    if ctx.config.fullscreen != start_fullscreen {
                         draw_text_justified(
                             "Restart required to apply change.",
-                            vec2( self.window_context.active_screen_size.x - 50.0, 125.0),
+                            vec2( ctx.window_context.active_screen_size.x - 50.0, 125.0),
                             TextParams {
                                 font,
                                 font_size: 28,
@@ -743,238 +1091,246 @@ impl Scene for MainMenuScene {
                         );
                     }
 
-                    nine_slice_frame.draw(justify_rect(self.window_context.active_screen_size.x - 250.0, 50.0, 200.0, 40.0, vec2(0.0, 0.5)), WHITE);
+                    nine_slice_frame.draw(justify_rect(ctx.window_context.active_screen_size.x - 250.0, 50.0, 200.0, 40.0, vec2(0.0, 0.5)), WHITE);
 
-                    draw_text_justified("Scaling: ", vec2(self.window_context.active_screen_size.x - 240.0, 50.0), TextParams {
+                    draw_text_justified("Scaling: ", vec2(ctx.window_context.active_screen_size.x - 240.0, 50.0), TextParams {
                         font,
                         font_size: 45,
                         font_scale: 0.25,
                         ..Default::default()
                     }, vec2(0.0, 0.5));
 
-                    if element_template(justify_rect(self.window_context.active_screen_size.x - 250.0 + 100.0, 50.0, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
-                        config.resolution_scale -= 1;
-                        config.resolution_scale = config.resolution_scale.clamp(1, 6);
+                    if element_template(justify_rect(ctx.window_context.active_screen_size.x - 250.0 + 100.0, 50.0, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
+                        ctx.config.resolution_scale -= 1;
+                        ctx.config.resolution_scale = ctx.config.resolution_scale.clamp(1, MAX_RESOLUTION_SCALE);
 
-                        self.window_context.scale = config.resolution_scale;
-                        self.window_context.dirty = true;
+                        ctx.window_context.scale = ctx.config.resolution_scale;
+                        ctx.window_context.dirty = true;
 
-                        let mut data = File::create("assets/config.json").unwrap();
-                        data.write_all((serde_json::to_string_pretty(&config).unwrap()).as_ref()).unwrap();
-                        config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
+                        ctx.config = save_config(&ctx.config);
                     }
 
-                    draw_text_justified(&format!("{}", config.resolution_scale), vec2(self.window_context.active_screen_size.x - 110.0, 50.0), TextParams {
+                    draw_text_justified(&format!("{}", ctx.config.resolution_scale), vec2(ctx.window_context.active_screen_size.x - 110.0, 50.0), TextParams {
                         font,
                         font_size: 45,
                         font_scale: 0.25,
                         ..Default::default()
                     }, vec2(0.5, 0.5));
 
-                    if element_template(justify_rect(self.window_context.active_screen_size.x - 90.0, 50.0, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
-                        config.resolution_scale += 1;
-                        config.resolution_scale = config.resolution_scale.clamp(1, 16);
+                    if element_template(justify_rect(ctx.window_context.active_screen_size.x - 90.0, 50.0, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
+                        ctx.config.resolution_scale += 1;
+                        ctx.config.resolution_scale = ctx.config.resolution_scale.clamp(1, MAX_RESOLUTION_SCALE);
 
-                        self.window_context.scale = config.resolution_scale;
-                        self.window_context.dirty = true;
+                        ctx.window_context.scale = ctx.config.resolution_scale;
+                        ctx.window_context.dirty = true;
 
-                        let mut data = File::create("assets/config.json").unwrap();
-                        data.write_all((serde_json::to_string_pretty(&config).unwrap()).as_ref()).unwrap();
-                        config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
+                        ctx.config = save_config(&ctx.config);
                     }
 
-                    nine_slice_frame.draw(
-                        justify_rect(45.0, 150.0,
-                                     192.0, 175.0,
-                                     vec2(0.0, 0.0)), WHITE);
+                    nine_slice_frame.draw(justify_rect(ctx.window_context.active_screen_size.x - 250.0, 160.0, 200.0, 40.0, vec2(0.0, 0.5)), WHITE);
 
-                    draw_text_justified("Left Arrow: ",
-                                        vec2(45.0 + 10.0, 175.0),
-                                        TextParams {
+                    draw_text_justified("Scroll: ", vec2(ctx.window_context.active_screen_size.x - 240.0, 160.0), TextParams {
                         font,
-                        font_size: 40,
+                        font_size: 45,
                         font_scale: 0.25,
                         ..Default::default()
-                    },
-                                        vec2(0.0, 0.5));
+                    }, vec2(0.0, 0.5));
+
+                    if element_template(justify_rect(ctx.window_context.active_screen_size.x - 250.0 + 100.0, 160.0, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
+                        ctx.config.scroll_speed = ((ctx.config.scroll_speed - SCROLL_SPEED_STEP) / SCROLL_SPEED_STEP).round() * SCROLL_SPEED_STEP;
+                        ctx.config.scroll_speed = ctx.config.scroll_speed.clamp(MIN_SCROLL_SPEED, MAX_SCROLL_SPEED);
+
+                        ctx.config = save_config(&ctx.config);
+                    }
+
+                    draw_text_justified(&format!("{:.1}x", ctx.config.scroll_speed), vec2(ctx.window_context.active_screen_size.x - 110.0, 160.0), TextParams {
+                        font,
+                        font_size: 45,
+                        font_scale: 0.25,
+                        ..Default::default()
+                    }, vec2(0.5, 0.5));
+
+                    if element_template(justify_rect(ctx.window_context.active_screen_size.x - 90.0, 160.0, 18.0, 18.0, vec2(0.0, 0.5)), plus_template, mouse_pos).clicked() {
+                        ctx.config.scroll_speed = ((ctx.config.scroll_speed + SCROLL_SPEED_STEP) / SCROLL_SPEED_STEP).round() * SCROLL_SPEED_STEP;
+                        ctx.config.scroll_speed = ctx.config.scroll_speed.clamp(MIN_SCROLL_SPEED, MAX_SCROLL_SPEED);
+
+                        ctx.config = save_config(&ctx.config);
+                    }
+
                     if element_text_template(
-                        justify_rect(227.0, 175.0, 70.0, 20.0, vec2(1.0, 0.5)),
-                        match checking_input {
-                            1 => faint_button_template,
-                            _ => button_template
-                        },
+                        justify_rect(ctx.window_context.active_screen_size.x - 50.0, 210.0, 96.0 * 2.0, 18.0 * 1.8, vec2(1.0, 0.5)),
+                        button_template,
                         mouse_pos,
-                        &format!("{:?}", u32_to_key_code(config.controls.left_arrow)),
+                        &format!("Constant Time: {}", match ctx.config.constant_time {
+                            true => { "On" }
+                            false => { "Off" }
+                        }),
                         TextParams {
                             font,
-                            font_size: 35,
+                            font_size: 45,
                             font_scale: 0.25,
                             ..Default::default()
                         }
                     ).clicked() {
-                        checking_input = 1
+                        ctx.config.constant_time = !ctx.config.constant_time;
+
+                        ctx.config = save_config(&ctx.config);
                     }
 
-                    draw_text_justified("Up Arrow: ",
-                                        vec2(45.0 + 10.0, 200.0),
-                                        TextParams {
-                                            font,
-                                            font_size: 40,
-                                            font_scale: 0.25,
-                                            ..Default::default()
-                                        },
-                                        vec2(0.0, 0.5));
-                    if element_text_template(
-                        justify_rect(227.0, 200.0, 70.0, 20.0, vec2(1.0, 0.5)),
-                        match checking_input {
-                            2 => faint_button_template,
-                            _ => button_template
-                        },
-                        mouse_pos,
-                        &format!("{:?}", u32_to_key_code(config.controls.up_arrow)),
+                    draw_text_justified(
+                        "Notes arrive in the same time at any BPM",
+                        vec2(ctx.window_context.active_screen_size.x - 50.0, 235.0),
                         TextParams {
                             font,
-                            font_size: 35,
+                            font_size: 28,
                             font_scale: 0.25,
                             ..Default::default()
-                        }
-                    ).clicked() {
-                        checking_input = 2
-                    }
+                        }, vec2(1.0, 1.0)
+                    );
 
-                    draw_text_justified("Right Arrow: ",
-                                        vec2(45.0 + 10.0, 225.0),
-                                        TextParams {
-                                            font,
-                                            font_size: 40,
-                                            font_scale: 0.25,
-                                            ..Default::default()
-                                        },
-                                        vec2(0.0, 0.5));
                     if element_text_template(
-                        justify_rect(227.0, 225.0, 70.0, 20.0, vec2(1.0, 0.5)),
-                        match checking_input {
-                            3 => faint_button_template,
-                            _ => button_template
-                        },
+                        justify_rect(ctx.window_context.active_screen_size.x - 50.0, 270.0, 96.0 * 2.0, 18.0 * 1.8, vec2(1.0, 0.5)),
+                        button_template,
                         mouse_pos,
-                        &format!("{:?}", u32_to_key_code(config.controls.right_arrow)),
+                        &format!("Ghost Taps: {}", ctx.config.ghost_taps.name()),
                         TextParams {
                             font,
-                            font_size: 35,
+                            font_size: 45,
                             font_scale: 0.25,
                             ..Default::default()
                         }
                     ).clicked() {
-                        checking_input = 3
+                        ctx.config.ghost_taps = ctx.config.ghost_taps.next();
+
+                        ctx.config = save_config(&ctx.config);
                     }
 
-                    draw_text_justified("Down Arrow: ",
-                                        vec2(45.0 + 10.0, 250.0),
-                                        TextParams {
-                                            font,
-                                            font_size: 40,
-                                            font_scale: 0.25,
-                                            ..Default::default()
-                                        },
-                                        vec2(0.0, 0.5));
-                    if element_text_template(
-                        justify_rect(227.0, 250.0, 70.0, 20.0, vec2(1.0, 0.5)),
-                        match checking_input {
-                            4 => faint_button_template,
-                            _ => button_template
+                    draw_text_justified(
+                        match ctx.config.ghost_taps {
+                            GhostTapPolicy::Punish => "Pressing a lane with no note costs health",
+                            GhostTapPolicy::NearNotes => "Only costs health with a note close by",
+                            GhostTapPolicy::Ignore => "Pressing a lane with no note is free"
                         },
-                        mouse_pos,
-                        &format!("{:?}", u32_to_key_code(config.controls.down_arrow)),
+                        vec2(ctx.window_context.active_screen_size.x - 50.0, 295.0),
                         TextParams {
                             font,
-                            font_size: 35,
+                            font_size: 28,
                             font_scale: 0.25,
                             ..Default::default()
-                        }
-                    ).clicked() {
-                        checking_input = 4
-                    }
+                        }, vec2(1.0, 1.0)
+                    );
 
-                    draw_text_justified("Ship Up: ",
-                                        vec2(45.0 + 10.0, 275.0),
-                                        TextParams {
-                                            font,
-                                            font_size: 40,
-                                            font_scale: 0.25,
-                                            ..Default::default()
-                                        },
-                                        vec2(0.0, 0.5));
                     if element_text_template(
-                        justify_rect(227.0, 275.0, 70.0, 20.0, vec2(1.0, 0.5)),
-                        match checking_input {
-                            5 => faint_button_template,
-                            _ => button_template
-                        },
+                        justify_rect(ctx.window_context.active_screen_size.x - 50.0, 330.0, 96.0 * 2.0, 18.0 * 1.8, vec2(1.0, 0.5)),
+                        button_template,
                         mouse_pos,
-                        &format!("{:?}", u32_to_key_code(config.controls.ship_up)),
+                        &format!("Ship Controls: {}", ctx.config.ship_controls.name()),
                         TextParams {
                             font,
-                            font_size: 35,
+                            font_size: 45,
                             font_scale: 0.25,
                             ..Default::default()
                         }
                     ).clicked() {
-                        checking_input = 5
+                        ctx.config.ship_controls = ctx.config.ship_controls.next();
+                        checking_input = 0;
+
+                        ctx.config = save_config(&ctx.config);
                     }
 
-                    draw_text_justified("Ship Down: ",
-                                        vec2(45.0 + 10.0, 300.0),
-                                        TextParams {
-                                            font,
-                                            font_size: 40,
-                                            font_scale: 0.25,
-                                            ..Default::default()
-                                        },
-                                        vec2(0.0, 0.5));
-                    if element_text_template(
-                        justify_rect(227.0, 300.0, 70.0, 20.0, vec2(1.0, 0.5)),
-                        match checking_input {
-                            6 => faint_button_template,
-                            _ => button_template
-                        },
-                        mouse_pos,
-                        &format!("{:?}", u32_to_key_code(config.controls.ship_down)),
+                    draw_text_justified(
+                        ctx.config.ship_controls.description(),
+                        vec2(ctx.window_context.active_screen_size.x - 50.0, 355.0),
                         TextParams {
                             font,
-                            font_size: 35,
+                            font_size: 28,
                             font_scale: 0.25,
                             ..Default::default()
+                        }, vec2(1.0, 1.0)
+                    );
+
+                    nine_slice_frame.draw(
+                        justify_rect(45.0, 150.0,
+                                     192.0, 175.0,
+                                     vec2(0.0, 0.0)), WHITE);
+
+                    let mut save = false;
+                    // Only the ship bindings the current scheme uses are shown
+                    let shown_actions: Vec<usize> = match ctx.config.ship_controls {
+                        ShipControlScheme::Cycle => (0..6).collect(),
+                        ShipControlScheme::DirectLanes => (0..4).chain(6..10).collect(),
+                        ShipControlScheme::Mouse | ShipControlScheme::FollowLanePress => (0..4).collect()
+                    };
+                    for (row, action) in shown_actions.into_iter().enumerate() {
+                        let y = 170.0 + row as f32 * 20.0;
+                        let name = Controls::ACTION_NAMES[action];
+
+                        draw_text_justified(&format!("{}: ", name),
+                                            vec2(45.0 + 10.0, y),
+                                            TextParams {
+                                                font,
+                                                font_size: 40,
+                                                font_scale: 0.25,
+                                                ..Default::default()
+                                            },
+                                            vec2(0.0, 0.5));
+
+                        let bindings = ctx.config.controls.action(action);
+                        let bindings_text = match (checking_input == action + 1, bindings.len()) {
+                            (true, _) => "Press...".to_string(),
+                            (false, 0 | 1) => primary_binding_name(bindings),
+                            (false, count) => format!("{} +{}", primary_binding_name(bindings), count - 1)
+                        };
+                        if element_text_template(
+                            justify_rect(207.0, y, 70.0, 18.0, vec2(1.0, 0.5)),
+                            match checking_input == action + 1 {
+                                true => faint_button_template,
+                                false => button_template
+                            },
+                            mouse_pos,
+                            &bindings_text,
+                            TextParams {
+                                font,
+                                font_size: 35,
+                                font_scale: 0.25,
+                                ..Default::default()
+                            }
+                        ).clicked() {
+                            checking_input = action + 1
+                        }
+
+                        if element_template(justify_rect(213.0, y, 18.0, 8.0, vec2(0.0, 0.5)), minus_template, mouse_pos).clicked() {
+                            ctx.config.controls.action_mut(action).clear();
+                            checking_input = 0;
+                            save = true;
                         }
-                    ).clicked() {
-                        checking_input = 6
                     }
 
+                    draw_text_justified(
+                        "Click to add a key or gamepad button, press a bound one to remove it",
+                        vec2(45.0, 340.0),
+                        TextParams {
+                            font,
+                            font_size: 28,
+                            font_scale: 0.25,
+                            ..Default::default()
+                        }, vec2(0.0, 1.0)
+                    );
 
-                    let mut save = false;
                     if checking_input != 0 {
-                        if let Some(pressed_key) = get_last_key_pressed() {
-                            let code = key_code_to_u32(pressed_key);
-                            if code != 120 { // An Unknown Key
-                                match checking_input {
-                                    1 => { config.controls.left_arrow = code; }
-                                    2 => { config.controls.up_arrow = code; }
-                                    3 => { config.controls.right_arrow = code; }
-                                    4 => { config.controls.down_arrow = code; }
-                                    5 => { config.controls.ship_up = code; }
-                                    6 => { config.controls.ship_down = code; }
-                                    _ => {}
-                                };
-                                checking_input = 0;
-                                save = true;
+                        if let Some(binding) = ctx.input.last_pressed() {
+                            let bindings = ctx.config.controls.action_mut(checking_input - 1);
+                            match bindings.iter().position(|x| *x == binding) {
+                                Some(idx) => { bindings.remove(idx); }
+                                None => bindings.push(binding)
                             }
+                            checking_input = 0;
+                            save = true;
                         }
                     }
 
                     if save {
-                        let mut data = File::create("assets/config.json").unwrap();
-                        data.write_all((serde_json::to_string_pretty(&config).unwrap()).as_ref()).unwrap();
-                        config = serde_json::from_str::<Config>(&load_string("assets/config.json").await.unwrap()).unwrap();
+                        ctx.config = save_config(&ctx.config);
                     }
                 }
                 MenuState::Loading => {
@@ -999,46 +1355,107 @@ impl Scene for MainMenuScene {
                 }
             }
 
+            // Song Previews
+            let browsing_song_idx = match state {
+                MenuState::PlayMenu | MenuState::Loading => Some(chosen_song_idx),
+                _ => None
+            };
+            if browsing_song_idx != browsed_song_idx {
+                browsed_song_idx = browsing_song_idx;
+
+                let preview = match browsing_song_idx {
+                    Some(song_idx) => {
+                        let chart_path = format!("assets/songs/{}/{}", active_difficulty, song_database.songs[song_idx].json_name);
+                        play_preview(ctx, &chart_path, &song_database.songs[song_idx]).await
+                    }
+                    None => None
+                };
+
+                match preview {
+                    Some(preview) => {
+                        std::mem::replace(&mut music, preview).fade_out(PREVIEW_CROSSFADE_TIME);
+                        previewing = true;
+                    }
+                    None if previewing => {
+                        let settings = StreamingSoundSettings::new().fade_in_tween(fade_tween(PREVIEW_CROSSFADE_TIME));
+                        let menu_music = match ctx.audio.play_music_with(MENU_MUSIC_PATH, settings) {
+                            Ok(music) => music,
+                            Err(err) => return SceneChange::error(&err.to_string())
+                        };
+                        std::mem::replace(&mut music, menu_music).fade_out(PREVIEW_CROSSFADE_TIME);
+                        previewing = false;
+                    }
+                    None => {}
+                }
+            }
+
             load_scene_timer.update();
             load_watch_timer.update();
 
             if load_scene_timer.running {
-                music.set_volume(config.volume * (1.0 - load_scene_timer.percent_done()) as f64, Default::default()).unwrap();
+                music.set_volume((1.0 - load_scene_timer.percent_done()) as f64, Default::default()).unwrap();
             }
 
             if load_watch_timer.running {
-                music.set_volume(config.volume * (1.0 - load_watch_timer.percent_done()) as f64, Default::default()).unwrap();
+                music.set_volume((1.0 - load_watch_timer.percent_done()) as f64, Default::default()).unwrap();
+            }
+
+            if load_scene_timer.is_done() || load_watch_timer.is_done() {
+                // Come back to the song list with this song still picked
+                self.menu_state = MenuState::PlayMenu;
+                self.selected_difficulty = Some(active_difficulty.clone());
+                self.selected_song_idx = Some(chosen_song_idx);
             }
 
             if load_scene_timer.is_done() {
-                return Some(Box::new(NoteGameplayScene::new(
-                    self.window_context.clone(),
-                    format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name).as_str(),
-                    ReturnTo::MainMenu(active_difficulty.clone(), chosen_song_idx)
-                )));
+                self.modifiers = self.modifiers.reseeded();
+                let song_path = format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name);
+
+                if practicing {
+                    return SceneChange::Push(Box::new(NoteGameplayScene::practice(
+                        &song_path,
+                        self.modifiers,
+                        practice_range
+                    )), Transition::Fade);
+                }
+
+                return SceneChange::Push(Box::new(NoteGameplayScene::new(
+                    &song_path,
+                    self.modifiers
+                )), Transition::Fade);
             }
 
             if load_watch_timer.is_done() {
-                return Some(Box::new(PorpusScene::new(
-                    self.window_context.clone(),
-                    format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name).as_str(),
-                    ReturnTo::MainMenu(active_difficulty.clone(), chosen_song_idx)
-                )));
+                return SceneChange::Push(Box::new(PorpusScene::new(
+                    format!("assets/songs/{}/{}", active_difficulty, song_database.songs[chosen_song_idx].json_name).as_str()
+                )), Transition::Fade);
             }
 
             if is_key_pressed(KeyCode::F12) {
-                return Some(Box::new(BeatmapEditorScene { window_context: self.window_context.clone(), song_path: Default::default() }));
+                return SceneChange::Push(Box::new(BeatmapEditorScene { song_path: Default::default() }), Transition::SlideLeft);
             }
 
             if is_key_pressed(KeyCode::T) {
-                return Some(Box::new(TutorialScene {
-                    window_context: self.window_context.clone()
-                }))
+                return SceneChange::Push(Box::new(TutorialScene), Transition::SlideLeft)
             }
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
 
             next_frame().await;
         }
     }
-}
\ No newline at end of file
+}
+
+/// Starts the preview of `song_data`, using the music of the chart at `chart_path`.
+/// Songs without a preview, or whose music can't be played, give None so the menu music plays instead.
+async fn play_preview(ctx: &mut GameContext, chart_path: &str, song_data: &SongData) -> Option<Music> {
+    let preview_start = song_data.preview_start?;
+    let chart = serde_json::from_str::<Song>(&load_string(chart_path).await.ok()?).ok()?;
+
+    let settings = StreamingSoundSettings::new()
+        .start_position(preview_start as f64)
+        .loop_region(preview_start as f64..(preview_start + song_data.preview_length) as f64)
+        .fade_in_tween(fade_tween(PREVIEW_CROSSFADE_TIME));
+
+    ctx.audio.play_music_with(&chart.song_filepath, settings).ok()
+}
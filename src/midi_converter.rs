@@ -1,14 +1,23 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 use macroquad::file::load_file;
 use macroquad::rand::rand;
 use midly::{MidiMessage, Smf, Timing, TrackEventKind};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use crate::audio::check_format;
 use crate::note_gameplay_scene::song::Song;
 
 pub struct MidiConverter {
     pub bpm: f32,
     pub midi_path: String,
     pub song_path: String,
+    // Any supported format, its length and name are taken from the file
+    pub music_path: String,
 }
 
 impl MidiConverter {
@@ -17,6 +26,13 @@ impl MidiConverter {
 
         let bpm = self.bpm;
 
+        check_format(&self.music_path).unwrap_or_else(|err| panic!("{}", err));
+        let song_length = music_length(&self.music_path);
+        let credits = Path::new(&self.music_path).file_stem()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default()
+            .to_string();
+
         let midi_data = load_file(&self.midi_path).await.unwrap();
         let smf = Smf::parse(&midi_data).unwrap();
         let ppq =  match smf.header.timing {
@@ -30,13 +46,21 @@ impl MidiConverter {
         let save_path = self.song_path;
 
         let mut song = Song {
-            song_filepath: "assets/songs/music_files/Goldn.wav".to_string(),
-            song_length: 159.0,
+            song_filepath: self.music_path,
+            song_length,
             bpm,
-            credits: "Goldn".to_string(),
+            credits,
             high_score: 0,
             notes: vec![],
             attacks: vec![],
+            special_attacks: vec![],
+            health_preset: None,
+            health_overrides: Default::default(),
+            scoring_overrides: Default::default(),
+            special_notes: vec![],
+            modded_high_scores: HashMap::new(),
+            best_grade: None,
+            lamp: Default::default(),
         };
 
         for track in &smf.tracks {
@@ -66,4 +90,37 @@ impl MidiConverter {
         let cloned_song = song.clone();
         file.write_all(serde_json::to_string_pretty(&cloned_song).unwrap().as_ref()).unwrap();
     }
-}
\ No newline at end of file
+}
+
+/// The length of the music at `path` in seconds, read from its header without decoding it
+fn music_length(path: &str) -> f32 {
+    let stream = MediaSourceStream::new(Box::new(File::open(path).unwrap()), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = Path::new(path).extension().and_then(|x| x.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut format = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .unwrap()
+        .format;
+
+    let track = format.default_track().unwrap();
+    let track_id = track.id;
+    let sample_rate = track.codec_params.sample_rate.unwrap();
+
+    let frames = match track.codec_params.n_frames {
+        Some(frames) => frames,
+        // Some mp3s don't say how long they are, the packets still do without being decoded
+        None => {
+            let mut frames = 0;
+            while let Ok(packet) = format.next_packet() {
+                if packet.track_id() == track_id {
+                    frames += packet.dur;
+                }
+            }
+            frames
+        }
+    };
+
+    frames as f32 / sample_rate as f32
+}
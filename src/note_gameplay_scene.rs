@@ -1,77 +1,100 @@
 use async_trait::async_trait;
-use kira::manager::backend::cpal::CpalBackend;
-use kira::manager::{AudioManager, AudioManagerSettings};
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::*;
 use std::fs::File;
 use std::io::Write;
 
+use crate::config::Config;
+use crate::note_gameplay_scene::attacks::ship_hitbox;
 use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::modifiers::Modifiers;
+use crate::note_gameplay_scene::health::HealthRules;
+use crate::note_gameplay_scene::holds::{ActiveHold, HoldEvent, HoldWindows};
+use crate::note_gameplay_scene::judgement_log::{Grade, Judgement, JudgementLog, Lamp};
+use crate::note_gameplay_scene::practice::PracticeLoop;
+use crate::note_gameplay_scene::scoring::ScoringRules;
+use crate::note_gameplay_scene::ship_controls::ShipInput;
 use crate::note_gameplay_scene::score_texts::ScoreType::Score;
 use crate::note_gameplay_scene::score_texts::{ScoreQuality, ScoreText, ScoreType};
-use crate::note_gameplay_scene::song::Song;
+use crate::note_gameplay_scene::song::{lane_position, Song, LANES_TOP_TO_BOTTOM};
+use crate::note_gameplay_scene::special_notes::{SpecialEvent, SpecialNoteJudge};
 
 use crate::game_end_scene::GameEndScene;
-use crate::main_menu_scene::{Difficulty, MainMenuScene};
 use thousands::Separable;
-use crate::beatmap_editor_scene::BeatmapEditorScene;
-use crate::error_scene::ErrorScene;
+use crate::audio::Sfx;
+use crate::game_context::GameContext;
 
 use crate::ui::draw_text_justified;
 use crate::utils::*;
-use crate::Scene;
+use crate::overlays::{PauseChoice, PauseMenu};
+use crate::scene::{Overlay, Scene, SceneChange};
+use crate::transition::Transition;
 
+pub mod attacks;
 pub mod constants;
+pub mod ghost_taps;
+pub mod health;
+pub mod holds;
+pub mod judgement_log;
+pub mod modifiers;
+pub mod practice;
 pub mod score_texts;
+pub mod scoring;
+pub mod ship_controls;
 pub mod song;
-
-#[derive(Clone)]
-pub enum ReturnTo {
-    MainMenu(Difficulty, usize),
-    Editor
-}
+pub mod special_notes;
 
 pub struct NoteGameplayScene {
-    pub window_context: WindowContext,
     pub song_path: String,
-    pub return_to: ReturnTo
+    pub modifiers: Modifiers,
+    // Goes to the results screen when the song ends instead of straight back
+    pub show_results: bool,
+    // Replays this section with health turned off instead of playing the song through
+    pub practice: Option<PracticeLoop>
 }
 
 impl NoteGameplayScene {
-    pub fn new(window_context: WindowContext, song_path: &str, return_to: ReturnTo) -> Self {
+    pub fn new(song_path: &str, modifiers: Modifiers) -> Self {
         Self {
-            window_context,
             song_path: song_path.to_string(),
-            return_to
+            modifiers,
+            show_results: true,
+            practice: None
+        }
+    }
+
+    pub fn practice(song_path: &str, modifiers: Modifiers, practice_loop: PracticeLoop) -> Self {
+        Self {
+            practice: Some(practice_loop),
+            ..Self::new(song_path, modifiers)
+        }
+    }
+
+    /// A fresh copy of this scene to restart the song with
+    fn restarted(&self, practice: Option<PracticeLoop>) -> Self {
+        Self {
+            practice,
+            show_results: self.show_results,
+            ..Self::new(&self.song_path, self.modifiers)
         }
     }
 }
 
 #[async_trait]
 impl Scene for NoteGameplayScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
         // Fonts
-        let font = match load_ttf_font("assets/fonts/pixel.ttf").await {
-            Ok(font) => font,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let font = ctx.assets.font;
 
         // Score Texts
         let mut score_texts: Vec<ScoreText> = vec![];
 
-        // Health
-        let mut health = MAX_HEALTH;
-
         // Score
         let mut score = 0;
-        let mut combo_multiplier = 1.0;
 
-        let mut perfect_notes = 0;
-        let mut good_notes = 0;
-        let mut ok_notes = 0;
-        let mut incorrect_notes = 0;
-        let mut missed_notes = 0;
+        // Combo Display
+        let mut last_combo = 0;
+        let mut combo_scale = 1.0;
+        let mut milestone_timer = Timer::new(COMBO_MILESTONE_TEXT_TIME, 0);
 
         // Color Changing
         let mut red_increasing = false;
@@ -84,55 +107,79 @@ impl Scene for NoteGameplayScene {
         // Load the Song
         let song_json = match load_string(self.song_path.as_str()).await {
             Ok(json) => json,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
         };
         let mut song = match serde_json::from_str::<Song>(song_json.as_str()) {
             Ok(json) => json,
-            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Song Format Incorrect")
         };
 
+        // Health
+        let health_rules = HealthRules::for_song(&song, &self.song_path);
+        let mut health = health_rules.max_health;
+        let scoring = ScoringRules::for_song(&song);
+
         let mut active_notes = song.notes.clone();
-        let mut song_attacks = song.attacks.clone();
+        let mut song_attacks = song.all_attacks();
+        let mut special_notes = song.special_notes.clone();
+        self.modifiers.apply_to_chart(&mut active_notes, &mut song_attacks, &mut special_notes);
+
+        // Practice mode only plays the notes in the loop, and puts them back each time it restarts
+        let chart_notes = active_notes.clone();
+        let chart_attacks = song_attacks.clone();
+        let chart_special_notes = special_notes.clone();
+        let mut practice_loop = self.practice;
+        let mut loop_accuracies: Vec<f32> = vec![];
+        // First judgement made in the current loop
+        let mut loop_start_entry = 0;
+
+        if let Some(practice_loop) = practice_loop {
+            active_notes = practice_loop.notes_in(&chart_notes);
+            song_attacks = practice_loop.attacks_in(&chart_attacks);
+            special_notes = practice_loop.special_notes_in(&chart_special_notes);
+        }
+        let mut special_judge = SpecialNoteJudge::new(&special_notes);
 
-        let mut drawn_holds = song.notes.clone();
+        let mut drawn_holds = active_notes.clone();
         drawn_holds.retain(|x| x.2 != 0.0);
-        let mut active_holds: Vec<(f32, f32, f32)> = vec![];
+        let mut active_holds: Vec<ActiveHold> = vec![];
         let mut hold_thickness_multi: f32 = 1.0;
         let mut thickness_multi_growing: bool = true;
 
         let beats_per_second = song.bpm / 60.0;
-        let pixels_per_beat = (NOTE_START_POS - ARROW_OFFSET) / BEATS_TO_NOTE_HIT;
-
-        let mut sound_manager =
-            AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-        let sound =
-            StaticSoundData::from_file(song.song_filepath.clone(), StaticSoundSettings::default())
-                .unwrap();
-
-        let mut music = sound_manager.play(sound).unwrap();
-
-        let config =
-            match serde_json::from_str::<Config>(&match load_string("assets/config.json").await {
-                Ok(text) => text,
-                Err(_) => return Some(Box::new(ErrorScene::new("Config File Missing", self.window_context.clone())))
-            }) {
-                Ok(config) => config,
-                Err(_) => return Some(Box::new(ErrorScene::new("Config File Error", self.window_context.clone())))
-            };
 
-        music.set_volume(config.volume, Default::default()).unwrap();
+        // The hit windows are in beats, so they're widened with the rate to stay the same length in real time
+        let note_correct_range = NOTE_CORRECT_RANGE * self.modifiers.rate;
+        let perfect_hit_range = PERFECT_HIT_RANGE * self.modifiers.rate;
+        let good_hit_range = GOOD_HIT_RANGE * self.modifiers.rate;
+        let ghost_tap_near_range = GHOST_TAP_NEAR_RANGE * self.modifiers.rate;
+        let hold_windows = HoldWindows::new(self.modifiers.rate);
+
+        let mut judgements = JudgementLog::new(NOTE_CORRECT_RANGE / beats_per_second);
+        // How many judgements and lasers taken have had their sound played
+        let mut sounded_entries = 0;
+        let mut sounded_lasers = 0;
+        // Beat of the last frame, to find the lasers that started warming up or fired since
+        let mut last_sound_beat: Option<f32> = None;
+
+        let mut music = match ctx.audio.play_music(&song.song_filepath) {
+            Ok(music) => music,
+            Err(err) => return SceneChange::error(&err.to_string())
+        };
+        music.set_playback_rate(self.modifiers.rate as f64, Default::default()).unwrap();
+        if let Some(practice_loop) = practice_loop {
+            music.seek_to((practice_loop.lead_in_beat() / beats_per_second) as f64).unwrap();
+        }
+
+        let config = ctx.config.clone();
+
+        // With constant time scrolling the rate also changes how many beats a note is on screen for
+        let pixels_per_beat = scroll_pixels_per_beat(&config, song.bpm * self.modifiers.rate);
 
         // Background
-        let background_texture =
-            match quick_load_texture("assets/images/backgrounds/Space Background (3).png").await {
-                Ok(texture) => texture,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            };
+        let background_texture = ctx.assets.gameplay_background;
 
-        let ship = match quick_load_texture("assets/images/ship.png").await  {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let ship = ctx.assets.ship;
         let mut ship_position = SHIP_FAR_RIGHT / 2.0;
         let mut ship_height = 200.0;
         let mut wanted_ship_height = RIGHT_ARROW_POS;
@@ -142,59 +189,84 @@ impl Scene for NoteGameplayScene {
         let mut ship_alpha_growing = false;
 
         // Input Notes
-        let input_note_up = match quick_load_texture("assets/images/arrow_up.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_down = match quick_load_texture("assets/images/arrow_down.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_left = match quick_load_texture("assets/images/arrow_left.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_right = match quick_load_texture("assets/images/arrow_right.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let input_note_up = ctx.assets.arrow_up;
+        let input_note_down = ctx.assets.arrow_down;
+        let input_note_left = ctx.assets.arrow_left;
+        let input_note_right = ctx.assets.arrow_right;
 
         let mut up_scale = 1.0;
         let mut down_scale = 1.0;
         let mut left_scale = 1.0;
         let mut right_scale = 1.0;
 
-        let hold_note = match quick_load_texture("assets/images/hold.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let laser = match quick_load_texture("assets/images/laser.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let hold_note = ctx.assets.hold;
+        let laser = ctx.assets.laser;
+
+        let mut resume_timer = Timer::new(RESUME_COUNTDOWN, 0);
+        // Music position at the last frame, None after a seek since it only lands a frame later
+        let mut last_position: Option<f64> = None;
+        // The window stopped drawing while the music played on, so the game pauses at the end of the frame
+        let mut focus_lost = false;
 
         let mut game_over_timer = Timer::new(3.0, 0);
 
-        let up_control = u32_to_key_code(config.controls.up_arrow);
-        let down_control = u32_to_key_code(config.controls.down_arrow);
-        let left_control = u32_to_key_code(config.controls.left_arrow);
-        let right_control = u32_to_key_code(config.controls.right_arrow);
+        let up_control = &config.controls.up_arrow;
+        let down_control = &config.controls.down_arrow;
+        let left_control = &config.controls.left_arrow;
+        let right_control = &config.controls.right_arrow;
+
+        let ship_up_control = &config.controls.ship_up;
+        let ship_down_control = &config.controls.ship_down;
+        let ship_lane_controls = [
+            (2.0, &config.controls.ship_to_left),
+            (3.0, &config.controls.ship_to_up),
+            (1.0, &config.controls.ship_to_right),
+            (4.0, &config.controls.ship_to_down)
+        ];
 
-        let ship_up_control = u32_to_key_code(config.controls.ship_up);
-        let ship_down_control = u32_to_key_code(config.controls.ship_down);
 
         let mut fps_display = false;
 
         loop {
+            ctx.input.update();
+
             clear_background(BLACK);
-            set_camera(&self.window_context.camera);
+            set_camera(&ctx.window_context.camera);
             clear_background(DARKGRAY);
 
             draw_texture(background_texture, 0.0, 0.0, Color::new(0.5, 0.5, 0.5, 1.0));
 
+            // Macroquad doesn't say when the window loses focus, but the frames stop coming while it's minimized,
+            // dragged or in the background and the music keeps going, so a jump in the music means the player missed it.
+            // The music goes back to where they last saw it and the frame is skipped so nothing in the gap is judged.
+            let position = music.position();
+            if let Some(seen_position) = last_position {
+                if !game_over_timer.running && position - seen_position > FOCUS_LOSS_GAP * self.modifiers.rate as f64 {
+                    music.pause(Default::default()).unwrap();
+                    music.seek_to(seen_position).unwrap();
+                    focus_lost = true;
+                    last_position = None;
+
+                    ctx.draw_window();
+                    next_frame().await;
+                    continue;
+                }
+            }
+            last_position = Some(position);
+
             let beat =
                 beats_per_second * ((music.position() * 1_000_000.0).round() / 1_000_000.0) as f32;
 
+            // Nothing is judged while paused or counting down to resume
+            let frozen = resume_timer.running || focus_lost;
+
+            let up_pressed = !frozen && ctx.input.is_pressed(up_control);
+            let down_pressed = !frozen && ctx.input.is_pressed(down_control);
+            let left_pressed = !frozen && ctx.input.is_pressed(left_control);
+            let right_pressed = !frozen && ctx.input.is_pressed(right_control);
+            let ship_up_pressed = !frozen && ctx.input.is_pressed(ship_up_control);
+            let ship_down_pressed = !frozen && ctx.input.is_pressed(ship_down_control);
+
             // Color Fixing
             red_value += get_frame_time()
                 * 2.0
@@ -254,200 +326,65 @@ impl Scene for NoteGameplayScene {
             let mut hit_notes = vec![];
 
             for (note_beat, note_type, hold_length) in &active_notes {
-                let note_offset = match *note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => {
-                        panic!("Error! Note type: '{note_type}' unknown")
-                    }
-                };
-
                 if *note_beat < beat - 1.0 {
                     hit_notes.push((*note_beat, *note_type, *hold_length));
                     score_texts.push(ScoreText {
                         timer: TEXT_LAST_TIME,
                         score_type: ScoreType::Miss,
-                        y_offset: note_offset,
+                        y_offset: lane_position(*note_type),
                     });
-                    health -= HEALTH_LOSS_MISS;
-                    missed_notes += 1;
-                    combo_multiplier = 1.0;
+                    health -= health_rules.miss_loss;
+                    judgements.record(*note_beat, *note_type, Judgement::Miss, None);
+                }
+            }
 
+            // Each press hits the closest note in its lane, wherever it is in the chart
+            let lane_presses = [(3.0, up_pressed), (4.0, down_pressed), (2.0, left_pressed), (1.0, right_pressed)];
+            for (lane, pressed) in lane_presses {
+                if !pressed {
                     continue;
                 }
 
-                if *note_beat < beat - NOTE_CORRECT_RANGE
-                    || *note_beat > beat + NOTE_CORRECT_RANGE
-                {
+                let closest_note = active_notes.iter()
+                    .filter(|x| x.1.floor() == lane && !hit_notes.contains(*x))
+                    .filter(|x| (x.0 - beat).abs() <= note_correct_range)
+                    .min_by(|a, b| (a.0 - beat).abs().total_cmp(&(b.0 - beat).abs()))
+                    .copied();
+                let Some((note_beat, note_type, hold_length)) = closest_note else {
                     continue;
-                }
+                };
 
-                let diff = note_beat - beat;
-                if is_key_pressed(up_control) && !correct_up && note_type.floor() == 3.0 {
-                    hit_notes.push((*note_beat, *note_type, *hold_length));
-                    correct_up = true;
-
-                    if *hold_length != 0.0 {
-                        active_holds.push((
-                            *note_beat,
-                            *note_type,
-                            *hold_length,
-                        ));
-                    } else {
-                        health += CORRECT_HEALTH_GAIN;
-
-                        if diff <= PERFECT_HIT_RANGE {
-                            score += (PERFECT_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.05;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Perfect),
-                                y_offset: note_offset,
-                            });
-                            perfect_notes += 1;
-                        } else if diff <= GOOD_HIT_RANGE {
-                            score += (GOOD_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.025;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Good),
-                                y_offset: note_offset,
-                            });
-                            good_notes += 1;
-                        } else {
-                            score += (OK_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Ok),
-                                y_offset: note_offset,
-                            });
-                            ok_notes += 1;
-                        }
-                    }
-                }
-                if is_key_pressed(down_control) && !correct_down && note_type.floor() == 4.0 {
-                    hit_notes.push((*note_beat, *note_type, *hold_length));
-                    correct_down = true;
-
-                    if *hold_length != 0.0 {
-                        active_holds.push((
-                            *note_beat,
-                            *note_type,
-                            *hold_length,
-                        ));
-                    } else {
-                        health += CORRECT_HEALTH_GAIN;
-
-                        if diff <= PERFECT_HIT_RANGE {
-                            score += (PERFECT_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.05;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Perfect),
-                                y_offset: note_offset,
-                            });
-                            perfect_notes += 1;
-                        } else if diff <= GOOD_HIT_RANGE {
-                            score += (GOOD_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.025;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Good),
-                                y_offset: note_offset,
-                            });
-                            good_notes += 1;
-                        } else {
-                            score += (OK_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Ok),
-                                y_offset: note_offset,
-                            });
-                            ok_notes += 1;
-                        }
-                    }
+                hit_notes.push((note_beat, note_type, hold_length));
+                match lane as i32 {
+                    3 => correct_up = true,
+                    4 => correct_down = true,
+                    2 => correct_left = true,
+                    1 => correct_right = true,
+                    _ => {}
                 }
-                if is_key_pressed(right_control) && !correct_right && note_type.floor() == 1.0 {
-                    hit_notes.push((*note_beat, *note_type, *hold_length));
-                    correct_right = true;
-
-                    if *hold_length != 0.0 {
-                        active_holds.push((
-                            *note_beat,
-                            *note_type,
-                            *hold_length,
-                        ));
-                    } else {
-                        health += CORRECT_HEALTH_GAIN;
-
-                        if diff <= PERFECT_HIT_RANGE {
-                            score += (PERFECT_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.05;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Perfect),
-                                y_offset: note_offset,
-                            });
-                            perfect_notes += 1;
-                        } else if diff <= GOOD_HIT_RANGE {
-                            score += (GOOD_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.025;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Good),
-                                y_offset: note_offset,
-                            });
-                            good_notes += 1;
-                        } else {
-                            score += (OK_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Ok),
-                                y_offset: note_offset,
-                            });
-                            ok_notes += 1;
-                        }
-                    }
-                }
-                if is_key_pressed(left_control) && !correct_left && note_type.floor() == 2.0 {
-                    hit_notes.push((*note_beat, *note_type, *hold_length));
-                    correct_left = true;
 
-                    if *hold_length != 0.0 {
-                        active_holds.push((*note_beat, *note_type, *hold_length));
-                    } else {
-                        health += CORRECT_HEALTH_GAIN;
+                health += health_rules.hit_gain;
 
-                        if diff <= PERFECT_HIT_RANGE {
-                            score += (PERFECT_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.05;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Perfect),
-                                y_offset: note_offset,
-                            });
-                            perfect_notes += 1;
-                        } else if diff <= GOOD_HIT_RANGE {
-                            score += (GOOD_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            combo_multiplier *= 1.025;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Good),
-                                y_offset: note_offset,
-                            });
-                            good_notes += 1;
-                        } else {
-                            score += (OK_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                            score_texts.push(ScoreText {
-                                timer: TEXT_LAST_TIME,
-                                score_type: Score(ScoreQuality::Ok),
-                                y_offset: note_offset,
-                            });
-                            ok_notes += 1;
-                        }
-                    }
+                let diff = (note_beat - beat).abs();
+                let offset = (beat - note_beat) / beats_per_second / self.modifiers.rate;
+                let (judgement, quality) = if diff <= perfect_hit_range {
+                    (Judgement::Perfect, ScoreQuality::Perfect)
+                } else if diff <= good_hit_range {
+                    (Judgement::Good, ScoreQuality::Good)
+                } else {
+                    (Judgement::Ok, ScoreQuality::Ok)
+                };
+
+                score += scoring.score(judgement, judgements.combo);
+                score_texts.push(ScoreText {
+                    timer: TEXT_LAST_TIME,
+                    score_type: Score(quality),
+                    y_offset: lane_position(note_type),
+                });
+                judgements.record(note_beat, note_type, judgement, Some(offset));
+
+                if hold_length != 0.0 {
+                    active_holds.push(ActiveHold::new((note_beat, note_type, hold_length)));
                 }
             }
 
@@ -456,72 +393,110 @@ impl Scene for NoteGameplayScene {
                     .retain(|x| x.0 != hit_note.0 || x.1 != hit_note.1 || x.2 != hit_note.2);
             }
 
-            // Check for missed notes
-            if is_key_pressed(up_control) && !correct_up {
-                health -= HEALTH_LOSS_INCORRECT;
-                score_texts.push(ScoreText {
-                    timer: TEXT_LAST_TIME,
-                    score_type: ScoreType::Incorrect,
-                    y_offset: UP_ARROW_POS,
-                });
-                combo_multiplier = 1.0;
-                incorrect_notes += 1;
-            }
-            if is_key_pressed(down_control) && !correct_down {
-                health -= HEALTH_LOSS_INCORRECT;
-                score_texts.push(ScoreText {
-                    timer: TEXT_LAST_TIME,
-                    score_type: ScoreType::Incorrect,
-                    y_offset: DOWN_ARROW_POS,
-                });
-                combo_multiplier = 1.0;
-                incorrect_notes += 1;
+            // Special Notes, they only get the presses the normal notes didn't use
+            let lane_inputs = [
+                (3.0, up_pressed && !correct_up, up_control),
+                (4.0, down_pressed && !correct_down, down_control),
+                (2.0, left_pressed && !correct_left, left_control),
+                (1.0, right_pressed && !correct_right, right_control)
+            ];
+            let special_presses: Vec<f32> = lane_inputs.iter().filter(|x| x.1).map(|x| x.0).collect();
+            let special_downs: Vec<f32> = lane_inputs.iter().filter(|x| !frozen && ctx.input.is_down(x.2)).map(|x| x.0).collect();
+            let ship_lane = LANES_TOP_TO_BOTTOM.iter()
+                .copied()
+                .find(|x| (lane_position(*x) - ship_height).abs() <= SHIP_TARGET_RANGE);
+
+            let (special_events, used_presses) = special_judge.update(
+                beat,
+                &special_presses,
+                &special_downs,
+                ship_lane,
+                hold_windows,
+                scoring.hold_tick_beats,
+            );
+            for used_press in used_presses {
+                match used_press as i32 {
+                    3 => correct_up = true,
+                    4 => correct_down = true,
+                    2 => correct_left = true,
+                    1 => correct_right = true,
+                    _ => {}
+                }
             }
-            if is_key_pressed(left_control) && !correct_left {
-                health -= HEALTH_LOSS_INCORRECT;
-                score_texts.push(ScoreText {
-                    timer: TEXT_LAST_TIME,
-                    score_type: ScoreType::Incorrect,
-                    y_offset: LEFT_ARROW_POS,
-                });
-                combo_multiplier = 1.0;
-                incorrect_notes += 1;
+
+            for event in special_events {
+                match event {
+                    SpecialEvent::Judged { beat: note_beat, lane, judgement, diff } => {
+                        score += scoring.score(judgement, judgements.combo);
+                        judgements.record(note_beat, lane, judgement, diff.map(|x| -x / beats_per_second / self.modifiers.rate));
+                        health += match judgement {
+                            Judgement::Miss => -health_rules.miss_loss,
+                            _ => health_rules.hit_gain
+                        };
+                        score_texts.push(ScoreText {
+                            timer: TEXT_LAST_TIME,
+                            score_type: match judgement {
+                                Judgement::Miss => ScoreType::Miss,
+                                Judgement::Good => Score(ScoreQuality::Good),
+                                Judgement::Ok => Score(ScoreQuality::Ok),
+                                _ => Score(ScoreQuality::Perfect)
+                            },
+                            y_offset: lane_position(lane),
+                        });
+                    }
+                    SpecialEvent::Tick { beat: tick_beat, lane } => {
+                        score += scoring.score(Judgement::HoldTick, judgements.combo);
+                        judgements.record(tick_beat, lane, Judgement::HoldTick, None);
+                    }
+                    SpecialEvent::MineHit { beat: mine_beat, lane } => {
+                        health -= health_rules.mine_loss;
+                        judgements.record(mine_beat, lane, Judgement::Incorrect, None);
+                        score_texts.push(ScoreText {
+                            timer: TEXT_LAST_TIME,
+                            score_type: ScoreType::Incorrect,
+                            y_offset: lane_position(lane),
+                        });
+                    }
+                }
             }
-            if is_key_pressed(right_control) && !correct_right {
-                health -= HEALTH_LOSS_INCORRECT;
+
+            // Check for presses that hit nothing
+            let missed_presses = [
+                (3.0, up_pressed && !correct_up),
+                (4.0, down_pressed && !correct_down),
+                (2.0, left_pressed && !correct_left),
+                (1.0, right_pressed && !correct_right)
+            ];
+            for (lane, missed) in missed_presses {
+                if !missed || !config.ghost_taps.punishes(beat, lane, &chart_notes, &chart_special_notes, ghost_tap_near_range) {
+                    continue;
+                }
+
+                health -= health_rules.incorrect_loss;
                 score_texts.push(ScoreText {
                     timer: TEXT_LAST_TIME,
                     score_type: ScoreType::Incorrect,
-                    y_offset: RIGHT_ARROW_POS,
+                    y_offset: lane_position(lane),
                 });
-                combo_multiplier = 1.0;
-                incorrect_notes += 1;
+                judgements.record(beat, lane, Judgement::Incorrect, None);
             }
 
             // Check for ship position changes
-            if is_key_pressed(ship_up_control) {
-                if wanted_ship_height == RIGHT_ARROW_POS {
-                    wanted_ship_height = UP_ARROW_POS;
-                } else if wanted_ship_height == UP_ARROW_POS {
-                    wanted_ship_height = LEFT_ARROW_POS;
-                } else if wanted_ship_height == DOWN_ARROW_POS {
-                    wanted_ship_height = RIGHT_ARROW_POS;
-                }
-            }
-            if is_key_pressed(ship_down_control) {
-                if wanted_ship_height == RIGHT_ARROW_POS {
-                    wanted_ship_height = DOWN_ARROW_POS;
-                } else if wanted_ship_height == LEFT_ARROW_POS {
-                    wanted_ship_height = UP_ARROW_POS;
-                } else if wanted_ship_height == UP_ARROW_POS {
-                    wanted_ship_height = RIGHT_ARROW_POS;
-                }
+            if !frozen {
+                let ship_input = ShipInput {
+                    up_pressed: ship_up_pressed,
+                    down_pressed: ship_down_pressed,
+                    lane_pressed: ship_lane_controls.iter().rev().find(|x| ctx.input.is_pressed(x.1)).map(|x| x.0),
+                    note_lane_pressed: lane_presses.iter().rev().find(|x| x.1).map(|x| x.0),
+                    mouse_y: ctx.window_context.camera.screen_to_world(mouse_position().into()).y
+                };
+                wanted_ship_height = config.ship_controls.wanted_height(wanted_ship_height, &ship_input);
             }
 
             ship_height += (wanted_ship_height - ship_height) * 6.0 * get_frame_time();
 
             // Draw the SHIP (AKA: Health Bar)!
-            let health_percentage = health as f32 / MAX_HEALTH as f32;
+            let health_percentage = health as f32 / health_rules.max_health as f32;
             let wanted_ship_position = (SHIP_FAR_RIGHT * health_percentage) - 150.0;
             ship_position += (wanted_ship_position - ship_position) * get_frame_time();
 
@@ -543,79 +518,67 @@ impl Scene for NoteGameplayScene {
 
             // Check For Hold notes failed or completed
             let mut remove_holds = vec![];
-            for (note_beat, note_type, hold_length) in &active_holds {
-                let note_offset = match *note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => {
-                        panic!("Error! Note type: '{note_type}' unknown")
-                    }
+            for active_hold in &mut active_holds {
+                let (note_beat, note_type, hold_length) = active_hold.note;
+                let note_offset = lane_position(note_type);
+
+                let held = match note_type as i32 {
+                    3 => ctx.input.is_down(up_control),
+                    4 => ctx.input.is_down(down_control),
+                    1 => ctx.input.is_down(right_control),
+                    2 => ctx.input.is_down(left_control),
+                    _ => false
                 };
 
-                let mut stay_active = false;
-                let percent_done = ((beat - note_beat) / hold_length).clamp(0.0, 1.0);
-                if is_key_down(up_control)
-                    && note_type.floor() == 3.0
-                {
-                    stay_active = true
-                }
-                if is_key_down(down_control)
-                    && note_type.floor() == 4.0
-                {
-                    stay_active = true
-                }
-                if is_key_down(right_control)
-                    && note_type.floor() == 1.0
-                {
-                    stay_active = true
-                }
-                if is_key_down(left_control)
-                    && note_type.floor() == 2.0
-                {
-                    stay_active = true
-                }
-
-                if stay_active && percent_done >= 1.0 {
-                    score += ((HOLD_SCORE_PER_BEAT as f32 / hold_length) * combo_multiplier).round()
-                        as i32;
-                    remove_holds.push((*note_beat, *note_type, *hold_length));
-                    combo_multiplier *= 1.08;
-                    score_texts.push(ScoreText {
-                        timer: TEXT_LAST_TIME,
-                        score_type: Score(ScoreQuality::Perfect),
-                        y_offset: note_offset,
-                    })
-                }
+                // Holds are kept while frozen so letting go to use the pause menu doesn't break them
+                for event in active_hold.update(beat, held || frozen, scoring.hold_tick_beats, hold_windows) {
+                    match event {
+                        HoldEvent::Tick(tick_beat) => {
+                            score += scoring.score(Judgement::HoldTick, judgements.combo);
+                            judgements.record(tick_beat, note_type, Judgement::HoldTick, None);
+                        }
+                        HoldEvent::Finished(judgement) => {
+                            score += scoring.score(judgement, judgements.combo);
+                            judgements.record(note_beat + hold_length, note_type, judgement, None);
+                            remove_holds.push(active_hold.note);
+                            score_texts.push(ScoreText {
+                                timer: TEXT_LAST_TIME,
+                                score_type: Score(match judgement {
+                                    Judgement::Good => ScoreQuality::Good,
+                                    Judgement::Ok => ScoreQuality::Ok,
+                                    _ => ScoreQuality::Perfect
+                                }),
+                                y_offset: note_offset,
+                            });
+                        }
+                        HoldEvent::Dropped => {
+                            let percent_done = ((beat - note_beat) / hold_length).clamp(0.0, 1.0);
 
-                if !stay_active && percent_done <= 1.0 {
-                    score += (((HOLD_SCORE_PER_BEAT as f32 / hold_length) * percent_done)
-                        * combo_multiplier)
-                        .round() as i32;
-                    remove_holds.push((*note_beat, *note_type, *hold_length));
-                    combo_multiplier *= 0.98;
-                    score_texts.push(ScoreText {
-                        timer: TEXT_LAST_TIME,
-                        score_type: Score(ScoreQuality::Ok),
-                        y_offset: note_offset,
-                    });
+                            health -= health_rules.miss_loss;
+                            judgements.record(beat, note_type, Judgement::Miss, None);
+                            remove_holds.push(active_hold.note);
+                            score_texts.push(ScoreText {
+                                timer: TEXT_LAST_TIME,
+                                score_type: ScoreType::Miss,
+                                y_offset: note_offset,
+                            });
 
-                    drawn_holds.push((
-                        beat,
-                        *note_type,
-                        (1.0 - percent_done) * *hold_length,
-                    ));
+                            // The rest of the hold keeps scrolling past
+                            drawn_holds.push((
+                                beat,
+                                note_type,
+                                (1.0 - percent_done) * hold_length,
+                            ));
+                        }
+                    }
                 }
             }
 
             for remove_hold in &remove_holds {
-                active_holds.retain(|x| x != remove_hold);
+                active_holds.retain(|x| x.note != *remove_hold);
                 drawn_holds.retain(|x| x != remove_hold);
             }
 
-            combo_multiplier = combo_multiplier.clamp(1.0, MAX_COMBO_MULTI);
-
             // Draw the active Holds
             let mut remove_holds = vec![];
             for (note_beat, note_type, hold_length) in &drawn_holds {
@@ -624,11 +587,9 @@ impl Scene for NoteGameplayScene {
                 let mut hold_width = hold_length * pixels_per_beat;
                 let hold_draw_pos = note_draw_pos + hold_width;
 
-                let is_active = active_holds.contains(&(
-                    *note_beat,
-                    *note_type,
-                    *hold_length,
-                ));
+                let active_hold = active_holds.iter().find(|x| x.note == (*note_beat, *note_type, *hold_length));
+                let is_active = active_hold.is_some();
+                let is_held = active_hold.map(|x| x.is_held()).unwrap_or(false);
 
                 if (hold_draw_pos <= 15.0 && is_active) || (hold_draw_pos <= -15.0 && !is_active) {
                     remove_holds.push((*note_beat, *note_type, *hold_length))
@@ -643,7 +604,7 @@ impl Scene for NoteGameplayScene {
                     hold_draw_pos,
                     hold_width,
                     hold_note,
-                    match is_active {
+                    match is_held {
                         true => hold_thickness_multi,
                         false => 1.0,
                     },
@@ -656,7 +617,7 @@ impl Scene for NoteGameplayScene {
 
             // Draw the active Notes
             for (note_beat, note_type, _hold_length) in &active_notes {
-                if *note_beat - beat < 15.0 {
+                if (*note_beat - beat) * pixels_per_beat < NOTE_START_POS {
                     let note_draw_pos =
                         ((note_beat - beat) * pixels_per_beat) + (ARROW_OFFSET - NOTE_SIZE / 2.0);
                     draw_note(
@@ -670,17 +631,26 @@ impl Scene for NoteGameplayScene {
                 }
             }
 
+            special_judge.draw(
+                beat,
+                pixels_per_beat,
+                input_note_left,
+                input_note_right,
+                input_note_up,
+                input_note_down,
+            );
+
             // Check Scale Up
-            if is_key_pressed(left_control) {
+            if left_pressed {
                 left_scale = ON_NOTE_PRESS_SCALE_FACTOR;
             }
-            if is_key_pressed(up_control) {
+            if up_pressed {
                 up_scale = ON_NOTE_PRESS_SCALE_FACTOR;
             }
-            if is_key_pressed(right_control) {
+            if right_pressed {
                 right_scale = ON_NOTE_PRESS_SCALE_FACTOR;
             }
-            if is_key_pressed(down_control) {
+            if down_pressed {
                 down_scale = ON_NOTE_PRESS_SCALE_FACTOR;
             }
 
@@ -766,80 +736,61 @@ impl Scene for NoteGameplayScene {
                 }
             }
 
-            ship_invincibility -= get_frame_time();
-
-            let mut remove_attacks = vec![];
-            for (attack_beat, last_length, note_type) in &song_attacks {
-                let note_offset = match *note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => {
-                        panic!("Error! Note type: '{note_type}' unknown")
-                    }
-                };
-
-                if *attack_beat + *last_length <= beat {
-                    remove_attacks.push((
-                        *attack_beat,
-                        *last_length,
-                        *note_type,
-                    ));
-                    continue;
-                }
-
-                if beat >= *attack_beat - 5.0 && beat <= *attack_beat {
-                    let difference = 5.0 - (*attack_beat - beat);
-
-                    draw_texture_ex(
-                        laser,
-                        0.0,
-                        note_offset - (40.0 * hold_thickness_multi) / 2.0,
-                        Color::new(red_value, green_value, blue_value, 1.0),
-                        DrawTextureParams {
-                            dest_size: Some(vec2(
-                                difference * difference * difference * 2.0,
-                                40.0 * hold_thickness_multi,
-                            )),
-                            ..Default::default()
-                        },
-                    );
-                }
+            if !frozen {
+                ship_invincibility -= get_frame_time();
+            }
 
-                if *attack_beat >= beat {
-                    continue;
-                }
+            song_attacks.retain(|x| x.end_beat() > beat);
 
-                draw_texture_ex(
-                    laser,
-                    0.0,
-                    note_offset - (40.0 * hold_thickness_multi) / 2.0,
-                    Color::new(red_value, green_value, blue_value, 1.0),
-                    DrawTextureParams {
-                        dest_size: Some(vec2(1000.0, 40.0 * hold_thickness_multi)),
-                        ..Default::default()
-                    },
-                );
+            let ship_hitbox = ship_hitbox(ship_position, ship_height);
+            for attack in &song_attacks {
+                attack.draw(beat, laser, Color::new(red_value, green_value, blue_value, 1.0), hold_thickness_multi);
 
-                if ship_height <= note_offset + 40.0
-                    && ship_height >= note_offset - 40.0
-                    && ship_invincibility <= 0.0
-                {
-                    health -= HEALTH_LOSS_LASER;
-                    score -= SCORE_LOSS_LASER;
+                if attack.hits(beat, ship_hitbox) && ship_invincibility <= 0.0 && !frozen {
+                    health -= health_rules.laser_loss;
+                    score -= scoring.laser_penalty;
+                    judgements.lasers_taken += 1;
                     score_texts.push(ScoreText {
                         timer: TEXT_LAST_TIME,
                         score_type: ScoreType::Miss,
-                        y_offset: note_offset,
+                        y_offset: ship_height,
                     });
 
                     ship_invincibility = 1.0;
                 }
             }
 
-            for remove_attack in &remove_attacks {
-                song_attacks.retain(|x| x != remove_attack);
+            // Sound Effects
+            let mut sounds: Vec<Sfx> = judgements.entries[sounded_entries..].iter()
+                .filter_map(|x| x.judgement.sound())
+                .collect();
+            sounded_entries = judgements.entries.len();
+            if judgements.lasers_taken > sounded_lasers {
+                sounds.push(Sfx::Miss);
+            }
+            sounded_lasers = judgements.lasers_taken;
+
+            if let Some(last_beat) = last_sound_beat {
+                let mut charging = false;
+                for attack in &song_attacks {
+                    let warmup_start = attack.beat - attack.warmup;
+                    // The charge is stretched to end as the laser fires
+                    if last_beat < warmup_start && beat >= warmup_start && !charging {
+                        charging = true;
+                        ctx.play_sfx_lasting(Sfx::LaserCharge, (attack.warmup / beats_per_second / self.modifiers.rate) as f64);
+                    }
+                    if last_beat < attack.beat && beat >= attack.beat {
+                        sounds.push(Sfx::LaserFire);
+                    }
+                }
+            }
+            last_sound_beat = Some(beat);
+
+            // Notes judged together only play their sound once
+            sounds.sort();
+            sounds.dedup();
+            for sfx in sounds {
+                ctx.play_sfx(sfx);
             }
 
             let mut remove_texts = vec![];
@@ -866,9 +817,24 @@ impl Scene for NoteGameplayScene {
                 vec2(0.0, 1.0),
             );
 
+            if !self.modifiers.is_default() {
+                draw_text_justified(
+                    format!("{} (x{:.2})", self.modifiers.tag(), self.modifiers.score_multiplier()).as_str(),
+                    vec2(5.0, 27.0),
+                    TextParams {
+                        font,
+                        font_size: 40,
+                        font_scale: 0.25,
+                        color: WHITE,
+                        ..Default::default()
+                    },
+                    vec2(0.0, 1.0),
+                );
+            }
+
             draw_text_justified(
                 song.credits.as_str(),
-                vec2(self.window_context.active_screen_size.x - 5.0, self.window_context.active_screen_size.y - 5.0),
+                vec2(ctx.window_context.active_screen_size.x - 5.0, ctx.window_context.active_screen_size.y - 5.0),
                 TextParams {
                     font,
                     font_size: 40,
@@ -886,7 +852,7 @@ impl Scene for NoteGameplayScene {
             if fps_display {
                 draw_text_justified(
                     format!("{}", get_fps()).as_str(),
-                    vec2(self.window_context.active_screen_size.x - 5.0, 5.0),
+                    vec2(ctx.window_context.active_screen_size.x - 5.0, 5.0),
                     TextParams {
                         font,
                         font_size: 40,
@@ -898,26 +864,188 @@ impl Scene for NoteGameplayScene {
                 );
             }
 
+            // Combo
+            if judgements.combo > last_combo {
+                combo_scale = COMBO_POP_SCALE;
+
+                if judgements.combo / COMBO_MILESTONE > last_combo / COMBO_MILESTONE {
+                    combo_scale = COMBO_MILESTONE_POP_SCALE;
+                    milestone_timer = Timer::new(COMBO_MILESTONE_TEXT_TIME, judgements.combo / COMBO_MILESTONE * COMBO_MILESTONE);
+                    milestone_timer.start();
+                }
+            }
+            last_combo = judgements.combo;
+            combo_scale = (combo_scale - get_frame_time() * SCALE_PER_SECOND_DECREASE).max(1.0);
+
+            if judgements.combo > 0 {
+                draw_text_justified(
+                    format!("{}", judgements.combo).as_str(),
+                    vec2(ctx.window_context.active_screen_size.x / 2.0, 30.0),
+                    TextParams {
+                        font,
+                        font_size: 100,
+                        font_scale: 0.25 * combo_scale,
+                        color: WHITE,
+                        ..Default::default()
+                    },
+                    vec2(0.5, 0.5),
+                );
+                draw_text_justified(
+                    format!("COMBO x{:.2}", scoring.multiplier(judgements.combo)).as_str(),
+                    vec2(ctx.window_context.active_screen_size.x / 2.0, 45.0),
+                    TextParams {
+                        font,
+                        font_size: 40,
+                        font_scale: 0.25,
+                        color: LIGHTGRAY,
+                        ..Default::default()
+                    },
+                    vec2(0.5, 1.0),
+                );
+            }
+
+            milestone_timer.update();
+            if milestone_timer.running && !milestone_timer.is_done() {
+                draw_text_justified(
+                    format!("{} COMBO!", milestone_timer.data).as_str(),
+                    vec2(ctx.window_context.active_screen_size.x / 2.0, 75.0 - 15.0 * milestone_timer.percent_done()),
+                    TextParams {
+                        font,
+                        font_size: 80,
+                        font_scale: 0.25,
+                        color: Color::new(1.0, 0.85, 0.3, 1.0 - milestone_timer.percent_done()),
+                        ..Default::default()
+                    },
+                    vec2(0.5, 0.5),
+                );
+            }
+
             // Clamp the health value to a max
-            health = health.clamp(0, MAX_HEALTH);
-
-            // Close Conditions
-            if is_key_pressed(KeyCode::Escape) {
-                return match self.return_to.clone() {
-                    ReturnTo::MainMenu(difficulty, song_idx) => {
-                        Some(Box::new(MainMenuScene {
-                            window_context: self.window_context.clone(),
-                            selected_difficulty: Some(difficulty),
-                            selected_song_idx: Some(song_idx)
-                        }))
+            health = health.clamp(0, health_rules.max_health);
+
+            // No Fail goes first so it can't undo a fail from the mods below, they're never on together anyway
+            if self.modifiers.no_fail {
+                health = health.max(1);
+            }
+            if self.modifiers.sudden_death && (judgements.count(Judgement::Miss) > 0 || judgements.count(Judgement::Incorrect) > 0 || judgements.lasers_taken > 0) {
+                health = 0;
+            }
+            if self.modifiers.perfect_only && (judgements.count(Judgement::Good) > 0 || judgements.count(Judgement::Ok) > 0) {
+                health = 0;
+            }
+
+            judgements.record_health(music.position() as f32, health);
+
+            // Pausing
+            if (is_key_pressed(KeyCode::Escape) || focus_lost) && !game_over_timer.running {
+                music.pause(Default::default()).unwrap();
+                focus_lost = false;
+
+                match PauseMenu.run(ctx).await {
+                    PauseChoice::Resume => {
+                        resume_timer = Timer::new(RESUME_COUNTDOWN, 0);
+                        resume_timer.start();
                     }
-                    ReturnTo::Editor => {
-                        Some(Box::new(BeatmapEditorScene {
-                            window_context: self.window_context.clone(),
-                            song_path: self.song_path.clone()
-                        }))
+                    PauseChoice::Restart => return SceneChange::Replace(Box::new(self.restarted(practice_loop)), Transition::Fade),
+                    PauseChoice::Quit => return SceneChange::Pop(Transition::Fade)
+                }
+            }
+
+            resume_timer.update();
+
+            if resume_timer.running {
+                draw_text_justified(
+                    format!("{}", resume_timer.timer.ceil().max(1.0)).as_str(),
+                    vec2(ctx.window_context.active_screen_size.x / 2.0, ctx.window_context.active_screen_size.y / 2.0),
+                    TextParams {
+                        font,
+                        font_size: 250,
+                        font_scale: 0.25 * (2.0 - resume_timer.timer.fract()),
+                        color: WHITE,
+                        ..Default::default()
+                    },
+                    vec2(0.5, 0.5),
+                );
+
+                if resume_timer.is_done() {
+                    music.resume(Default::default()).unwrap();
+                    resume_timer = Timer::new(RESUME_COUNTDOWN, 0);
+                }
+            }
+
+            // Practice
+            if let Some(current_loop) = practice_loop.as_mut() {
+                let mut restart_loop = false;
+
+                if !frozen {
+                    if is_key_pressed(KeyCode::LeftBracket) {
+                        current_loop.set_start(beat);
+                        restart_loop = true;
+                    }
+                    if is_key_pressed(KeyCode::RightBracket) {
+                        current_loop.set_end(beat);
+                        restart_loop = true;
+                    }
+                    if is_key_pressed(KeyCode::Backspace) {
+                        restart_loop = true;
+                    }
+                }
+
+                let loop_accuracy = judgements.accuracy_since(loop_start_entry);
+
+                // Only loops that were played through count towards the accuracy list
+                if beat >= current_loop.end_beat || music.position() >= song.song_length as f64 {
+                    if let Some(loop_accuracy) = loop_accuracy {
+                        loop_accuracies.push(loop_accuracy);
                     }
+                    restart_loop = true;
+                }
+
+                if restart_loop {
+                    active_notes = current_loop.notes_in(&chart_notes);
+                    song_attacks = current_loop.attacks_in(&chart_attacks);
+                    special_judge = SpecialNoteJudge::new(&current_loop.special_notes_in(&chart_special_notes));
+                    drawn_holds = active_notes.clone();
+                    drawn_holds.retain(|x| x.2 != 0.0);
+                    active_holds.clear();
+                    score_texts.clear();
+                    judgements.combo = 0;
+                    loop_start_entry = judgements.entries.len();
+
+                    music.seek_to((current_loop.lead_in_beat() / beats_per_second) as f64).unwrap();
+                    last_position = None;
+                }
+
+                let practice_text = |text: &str, y: f32| {
+                    draw_text_justified(text, vec2(5.0, y), TextParams {
+                        font,
+                        font_size: 40,
+                        font_scale: 0.25,
+                        color: WHITE,
+                        ..Default::default()
+                    }, vec2(0.0, 1.0));
+                };
+
+                practice_text(&format!("PRACTICE: Beats {} - {} ([ / ] to set, Backspace to restart)", current_loop.start_beat, current_loop.end_beat), 45.0);
+                practice_text(&format!("This Loop: {}", match loop_accuracy {
+                    Some(loop_accuracy) => format!("{:.1}%", loop_accuracy * 100.0),
+                    None => "-".to_string()
+                }), 60.0);
+                for (i, past_accuracy) in loop_accuracies.iter().enumerate().rev().take(5) {
+                    practice_text(&format!("Loop {}: {:.1}%", i + 1, past_accuracy * 100.0), 75.0 + 15.0 * (loop_accuracies.len() - 1 - i) as f32);
                 }
+
+                health = health_rules.max_health;
+
+                // The seek hasn't happened yet, so the music still reads past the loop until next frame
+                if restart_loop {
+                    ctx.draw_window();
+
+                    next_frame().await;
+                    continue;
+                }
+            } else if is_key_pressed(KeyCode::Backspace) && !game_over_timer.running {
+                return SceneChange::Replace(Box::new(self.restarted(None)), Transition::None);
             }
 
             if health <= 0 {
@@ -929,7 +1057,7 @@ impl Scene for NoteGameplayScene {
             if game_over_timer.running {
                 music
                     .set_playback_rate(
-                        1.0f64 - game_over_timer.percent_done() as f64,
+                        (1.0 - game_over_timer.percent_done() as f64) * self.modifiers.rate as f64,
                         Default::default(),
                     )
                     .unwrap();
@@ -937,8 +1065,8 @@ impl Scene for NoteGameplayScene {
                 draw_text_justified(
                     "GAME OVER",
                     vec2(
-                        self.window_context.active_screen_size.x / 2.0,
-                        (self.window_context.active_screen_size.y / 2.0)
+                        ctx.window_context.active_screen_size.x / 2.0,
+                        (ctx.window_context.active_screen_size.y / 2.0)
                             * game_over_timer.percent_done(),
                     ),
                     TextParams {
@@ -953,64 +1081,83 @@ impl Scene for NoteGameplayScene {
             }
 
             if game_over_timer.is_done() {
-                return match self.return_to.clone() {
-                    ReturnTo::MainMenu(_, _) => {
-                        Some(Box::new(GameEndScene {
-                            return_to: self.return_to.clone(),
-                            window_context: self.window_context.clone(),
-                            file_path: self.song_path.clone(),
-                            beat_level: false,
-                            score,
-                            perfect_notes,
-                            good_notes,
-                            ok_notes,
-                            incorrect_notes,
-                            missed_notes,
-                    }))}
-                    ReturnTo::Editor => { Some(Box::new(BeatmapEditorScene {
-                        window_context: self.window_context.clone(),
-                        song_path: self.song_path.clone()
-                    })) }
+                // Practice never saves results, health is kept full there anyway
+                if self.modifiers.is_default() && practice_loop.is_none() {
+                    song.best_grade = song.best_grade.max(Some(Grade::F));
+                    song.lamp = song.lamp.max(Lamp::Failed);
+
+                    let mut data = File::create(self.song_path.clone()).unwrap();
+                    data.write_all((serde_json::to_string_pretty(&song.clone()).unwrap()).as_ref())
+                        .unwrap();
+                }
+
+                if !self.show_results {
+                    return SceneChange::Pop(Transition::Fade);
                 }
+
+                return SceneChange::Replace(Box::new(GameEndScene {
+                    file_path: self.song_path.clone(),
+                    beat_level: false,
+                    score: (score as f32 * self.modifiers.score_multiplier()).round() as i32,
+                    modifiers: self.modifiers,
+                    judgements: judgements.clone(),
+                    health_rules,
+                }), Transition::Fade);
             }
 
-            if music.position() >= song.song_length as f64 {
-                if song.high_score < score {
-                    song.high_score = score;
+            // Practice loops back before the end instead of finishing the song
+            if music.position() >= song.song_length as f64 && practice_loop.is_none() {
+                let score = (score as f32 * self.modifiers.score_multiplier()).round() as i32;
+
+                // Modded scores are kept apart so they don't mix with normal high scores
+                if self.modifiers.is_default() {
+                    if song.high_score < score {
+                        song.high_score = score;
+                    }
+                    song.best_grade = song.best_grade.max(Some(judgements.grade(true)));
+                    song.lamp = song.lamp.max(judgements.lamp(true));
+                } else {
+                    let high_score = song.modded_high_scores.entry(self.modifiers.tag()).or_insert(0);
+                    if *high_score < score {
+                        *high_score = score;
+                    }
                 }
 
                 let mut data = File::create(self.song_path.clone()).unwrap();
                 data.write_all((serde_json::to_string_pretty(&song.clone()).unwrap()).as_ref())
                     .unwrap();
 
-                return match self.return_to.clone() {
-                    ReturnTo::MainMenu(_, _) => {
-                        Some(Box::new(GameEndScene {
-                            return_to: self.return_to.clone(),
-                            window_context: self.window_context.clone(),
-                            file_path: self.song_path.clone(),
-                            beat_level: true,
-                            score,
-                            perfect_notes,
-                            good_notes,
-                            ok_notes,
-                            incorrect_notes,
-                            missed_notes,
-                        }))}
-                    ReturnTo::Editor => { Some(Box::new(BeatmapEditorScene {
-                        window_context: self.window_context.clone(),
-                        song_path: self.song_path.clone()
-                    })) }
+                if !self.show_results {
+                    return SceneChange::Pop(Transition::Fade);
                 }
+
+                return SceneChange::Replace(Box::new(GameEndScene {
+                    file_path: self.song_path.clone(),
+                    beat_level: true,
+                    score,
+                    modifiers: self.modifiers,
+                    judgements: judgements.clone(),
+                    health_rules,
+                }), Transition::Fade);
             }
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
 
             next_frame().await;
         }
     }
 }
 
+/// How far notes move per beat with the player's scroll speed settings
+pub fn scroll_pixels_per_beat(config: &Config, bpm: f32) -> f32 {
+    let beats_to_note_hit = match config.constant_time {
+        true => CONSTANT_TIME_APPROACH * (bpm / 60.0),
+        false => BEATS_TO_NOTE_HIT
+    };
+
+    (NOTE_START_POS - ARROW_OFFSET) / (beats_to_note_hit / config.scroll_speed)
+}
+
 pub fn draw_note(
     direction: f32,
     location: f32,
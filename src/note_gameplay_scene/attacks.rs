@@ -0,0 +1,216 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::song::{lane_position, LANES_TOP_TO_BOTTOM};
+
+// Lasers are drawn well past the right edge of the screen
+const LASER_DRAW_WIDTH: f32 = 1000.0;
+// Projectiles fly in from the right edge of the screen
+const PROJECTILE_START_X: f32 = 708.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AttackKind {
+    // A full width laser on one lane, what every attack in Song::attacks is
+    Laser { lane: f32 },
+    // A laser that slides from one lane to another over the attack's length
+    MovingLaser { from_lane: f32, to_lane: f32 },
+    // Shots that fly down a lane, spread evenly over the attack's length
+    Projectiles { lane: f32, count: u32 },
+    // Lasers on every lane but the gap
+    Barrage { gap_lane: f32 }
+}
+
+impl AttackKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttackKind::Laser { .. } => "Laser",
+            AttackKind::MovingLaser { .. } => "Moving Laser",
+            AttackKind::Projectiles { .. } => "Projectiles",
+            AttackKind::Barrage { .. } => "Barrage"
+        }
+    }
+}
+
+fn default_warmup() -> f32 {
+    ATTACK_WARMUP_BEATS
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Attack {
+    pub beat: f32,
+    // Beats it fires for
+    pub length: f32,
+    // Beats of warning shown before it fires
+    #[serde(default = "default_warmup")]
+    pub warmup: f32,
+    pub kind: AttackKind
+}
+
+impl Attack {
+    /// An attack (Beat, Last Time, Type) from Song::attacks
+    pub fn from_legacy(attack: &(f32, f32, f32)) -> Self {
+        Self {
+            beat: attack.0,
+            length: attack.1,
+            warmup: ATTACK_WARMUP_BEATS,
+            kind: AttackKind::Laser { lane: attack.2 }
+        }
+    }
+
+    /// Last beat anything from this attack is on screen, projectiles keep flying after the last one is fired
+    pub fn end_beat(&self) -> f32 {
+        match self.kind {
+            AttackKind::Projectiles { .. } => self.beat + self.length + PROJECTILE_CROSS_BEATS,
+            _ => self.beat + self.length
+        }
+    }
+
+    /// Every lane this attack fires on at some point
+    pub fn lanes(&self) -> Vec<f32> {
+        match self.kind {
+            AttackKind::Laser { lane } | AttackKind::Projectiles { lane, .. } => vec![lane],
+            AttackKind::MovingLaser { from_lane, to_lane } => {
+                let (top, bottom) = (lane_position(from_lane).min(lane_position(to_lane)), lane_position(from_lane).max(lane_position(to_lane)));
+                LANES_TOP_TO_BOTTOM.iter()
+                    .copied()
+                    .filter(|x| lane_position(*x) >= top && lane_position(*x) <= bottom)
+                    .collect()
+            }
+            AttackKind::Barrage { gap_lane } => LANES_TOP_TO_BOTTOM.iter().copied().filter(|x| *x != gap_lane).collect()
+        }
+    }
+
+    pub fn map_lanes(&mut self, remap: impl Fn(f32) -> f32) {
+        match &mut self.kind {
+            AttackKind::Laser { lane } | AttackKind::Projectiles { lane, .. } => *lane = remap(*lane),
+            AttackKind::MovingLaser { from_lane, to_lane } => {
+                *from_lane = remap(*from_lane);
+                *to_lane = remap(*to_lane);
+            }
+            AttackKind::Barrage { gap_lane } => *gap_lane = remap(*gap_lane)
+        }
+    }
+
+    fn moving_laser_height(&self, beat: f32, from_lane: f32, to_lane: f32) -> f32 {
+        let percent = ((beat - self.beat) / self.length.max(0.001)).clamp(0.0, 1.0);
+        lane_position(from_lane) + (lane_position(to_lane) - lane_position(from_lane)) * percent
+    }
+
+    /// Beat each projectile is fired at
+    fn shot_beats(&self, count: u32) -> Vec<f32> {
+        let spacing = self.length / count.max(1) as f32;
+        (0..count).map(|shot| self.beat + shot as f32 * spacing).collect()
+    }
+
+    fn projectile_x(shot_beat: f32, beat: f32) -> f32 {
+        PROJECTILE_START_X - (beat - shot_beat) / PROJECTILE_CROSS_BEATS * (PROJECTILE_START_X + PROJECTILE_SIZE)
+    }
+
+    /// The areas that hurt the ship at `beat`
+    pub fn hitboxes(&self, beat: f32) -> Vec<Rect> {
+        if beat < self.beat || beat >= self.end_beat() {
+            return vec![];
+        }
+
+        let laser = |height: f32| Rect::new(0.0, height - LASER_HIT_HEIGHT / 2.0, LASER_DRAW_WIDTH, LASER_HIT_HEIGHT);
+
+        match self.kind {
+            AttackKind::Laser { lane } => vec![laser(lane_position(lane))],
+            AttackKind::MovingLaser { from_lane, to_lane } => vec![laser(self.moving_laser_height(beat, from_lane, to_lane))],
+            AttackKind::Barrage { gap_lane } => LANES_TOP_TO_BOTTOM.iter()
+                .filter(|x| **x != gap_lane)
+                .map(|x| laser(lane_position(*x)))
+                .collect(),
+            AttackKind::Projectiles { lane, count } => self.shot_beats(count).iter()
+                .filter(|x| beat >= **x && beat < **x + PROJECTILE_CROSS_BEATS)
+                .map(|x| Rect::new(Self::projectile_x(*x, beat), lane_position(lane) - PROJECTILE_SIZE / 2.0, PROJECTILE_SIZE, PROJECTILE_SIZE))
+                .collect()
+        }
+    }
+
+    pub fn hits(&self, beat: f32, ship_hitbox: Rect) -> bool {
+        self.hitboxes(beat).iter().any(|x| x.overlaps(&ship_hitbox))
+    }
+
+    /// Whether a ship at `height` could be hit at `beat`, wherever it is across the screen
+    pub fn blocks(&self, beat: f32, height: f32) -> bool {
+        self.hitboxes(beat).iter()
+            .any(|x| height + SHIP_HITBOX_HEIGHT / 2.0 > x.y && height - SHIP_HITBOX_HEIGHT / 2.0 < x.y + x.h)
+    }
+
+    /// Draws the warning while it warms up and the attack itself once it fires
+    pub fn draw(&self, beat: f32, laser_tex: Texture2D, color: Color, thickness_multi: f32) {
+        let thickness = 40.0 * thickness_multi;
+        let draw_laser = |height: f32, width: f32| {
+            draw_texture_ex(
+                laser_tex,
+                0.0,
+                height - thickness / 2.0,
+                color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(width, thickness)),
+                    ..Default::default()
+                },
+            );
+        };
+
+        if beat >= self.beat - self.warmup && beat < self.beat {
+            // Grows the same way whatever the warm-up length is
+            let difference = (1.0 - (self.beat - beat) / self.warmup.max(0.001)) * ATTACK_WARMUP_BEATS;
+            let width = difference * difference * difference * 2.0;
+
+            match self.kind {
+                AttackKind::Laser { lane } => draw_laser(lane_position(lane), width),
+                AttackKind::MovingLaser { from_lane, to_lane } => {
+                    draw_laser(lane_position(from_lane), width);
+                    draw_line(
+                        ARROW_OFFSET,
+                        lane_position(from_lane),
+                        ARROW_OFFSET,
+                        lane_position(to_lane),
+                        3.0,
+                        Color::new(color.r, color.g, color.b, 0.5),
+                    );
+                }
+                AttackKind::Barrage { gap_lane } => {
+                    for lane in LANES_TOP_TO_BOTTOM.iter().filter(|x| **x != gap_lane) {
+                        draw_laser(lane_position(*lane), width);
+                    }
+                }
+                AttackKind::Projectiles { lane, .. } => {
+                    draw_circle_lines(
+                        PROJECTILE_START_X - PROJECTILE_SIZE,
+                        lane_position(lane),
+                        PROJECTILE_SIZE * (1.0 - (self.beat - beat) / self.warmup.max(0.001)),
+                        2.0,
+                        color,
+                    );
+                }
+            }
+        }
+
+        if beat < self.beat || beat >= self.end_beat() {
+            return;
+        }
+
+        match self.kind {
+            AttackKind::Laser { lane } => draw_laser(lane_position(lane), LASER_DRAW_WIDTH),
+            AttackKind::MovingLaser { from_lane, to_lane } => draw_laser(self.moving_laser_height(beat, from_lane, to_lane), LASER_DRAW_WIDTH),
+            AttackKind::Barrage { gap_lane } => {
+                for lane in LANES_TOP_TO_BOTTOM.iter().filter(|x| **x != gap_lane) {
+                    draw_laser(lane_position(*lane), LASER_DRAW_WIDTH);
+                }
+            }
+            AttackKind::Projectiles { .. } => {
+                for hitbox in self.hitboxes(beat) {
+                    draw_circle(hitbox.center().x, hitbox.center().y, PROJECTILE_SIZE / 2.0 * thickness_multi, color);
+                }
+            }
+        }
+    }
+}
+
+/// The part of the ship attacks can hit, narrower than its sprite so grazing a laser doesn't count
+pub fn ship_hitbox(ship_position: f32, ship_height: f32) -> Rect {
+    Rect::new(ship_position, ship_height - SHIP_HITBOX_HEIGHT / 2.0, SHIP_PIXEL_SIZE, SHIP_HITBOX_HEIGHT)
+}
@@ -12,8 +12,17 @@ pub const NOTE_SIZE: f32 = 32.0;
 pub const NOTE_START_POS: f32 = 800.0;
 pub const BEATS_TO_NOTE_HIT: f32 = 8.0;
 
+// Scroll Speed
+pub const MIN_SCROLL_SPEED: f32 = 0.5;
+pub const MAX_SCROLL_SPEED: f32 = 3.0;
+pub const SCROLL_SPEED_STEP: f32 = 0.1;
+// Seconds a note takes to reach the arrows at 1.0x with constant time scrolling
+pub const CONSTANT_TIME_APPROACH: f32 = 3.2;
+
 // Note Hit Range
 pub const NOTE_CORRECT_RANGE: f32 = 0.25;
+// How close a note has to be for a press that missed it to count with the Near Notes ghost tap policy
+pub const GHOST_TAP_NEAR_RANGE: f32 = 1.0;
 
 // Health
 pub const MAX_HEALTH: i32 = 500;
@@ -21,6 +30,7 @@ pub const CORRECT_HEALTH_GAIN: i32 = 15;
 
 pub const HEALTH_LOSS_MISS: i32 = 50;
 pub const HEALTH_LOSS_INCORRECT: i32 = 50;
+pub const HEALTH_LOSS_MINE: i32 = 100;
 
 pub const HEALTH_LOSS_LASER: i32 = 300;
 pub const SCALE_ALPHA_PER_SECOND: f32 = 4.0;
@@ -36,10 +46,26 @@ pub const OK_HIT_SCORE: i32 = 500;
 pub const PERFECT_HIT_RANGE: f32 = 0.05;
 pub const GOOD_HIT_RANGE: f32 = 0.1;
 
-pub const HOLD_SCORE_PER_BEAT: i32 = 1500;
+// Holds give a tick every HOLD_TICK_BEATS while they're held down
+pub const HOLD_TICK_SCORE: i32 = 250;
+pub const HOLD_TICK_BEATS: f32 = 1.0;
+// How long a hold can be let go of before it's dropped, in beats
+pub const HOLD_REGRAB_RANGE: f32 = 0.5;
+
+// Longest gap between taps that keeps a roll going, in beats
+pub const ROLL_TAP_RANGE: f32 = 0.5;
 
+// The multiplier goes up by COMBO_MULTI_STEP every COMBO_PER_MULTI_STEP hits in a row
+pub const COMBO_PER_MULTI_STEP: u32 = 25;
+pub const COMBO_MULTI_STEP: f32 = 0.25;
 pub const MAX_COMBO_MULTI: f32 = 4.0;
 
+// Combo
+pub const COMBO_MILESTONE: u32 = 50;
+pub const COMBO_POP_SCALE: f32 = 1.25;
+pub const COMBO_MILESTONE_POP_SCALE: f32 = 2.0;
+pub const COMBO_MILESTONE_TEXT_TIME: f32 = 1.5;
+
 // Visual
 pub const ON_NOTE_PRESS_SCALE_FACTOR: f32 = 1.5;
 pub const SCALE_PER_SECOND_DECREASE: f32 = 2.0;
@@ -52,3 +78,22 @@ pub const SHIP_PIXEL_SIZE: f32 = 48.0;
 pub const SHIP_FAR_RIGHT: f32 = 650.0;
 
 pub const SCORE_LOSS_LASER: i32 = 500;
+
+// Attacks
+// Beats of warning an attack gets when its chart doesn't give one
+pub const ATTACK_WARMUP_BEATS: f32 = 5.0;
+pub const LASER_HIT_HEIGHT: f32 = 64.0;
+pub const SHIP_HITBOX_HEIGHT: f32 = 16.0;
+pub const PROJECTILE_SIZE: f32 = 16.0;
+// Beats a projectile takes to cross the screen
+pub const PROJECTILE_CROSS_BEATS: f32 = 2.0;
+
+// Time it takes the ship to leave one lane's laser for the next one over
+pub const SHIP_LANE_MOVE_TIME: f32 = 0.13;
+// How close the ship has to be to a lane's center to collect its ship targets
+pub const SHIP_TARGET_RANGE: f32 = 30.0;
+
+// Pausing
+pub const RESUME_COUNTDOWN: f32 = 3.0;
+// Seconds of music that can pass between two frames before the window is taken to have lost focus
+pub const FOCUS_LOSS_GAP: f64 = 0.25;
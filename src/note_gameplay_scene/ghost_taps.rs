@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::special_notes::SpecialNote;
+
+/// What happens when a lane is pressed and there's no note there to hit
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GhostTapPolicy {
+    // Every press that misses costs health
+    #[default]
+    Punish,
+    // Presses that miss are never counted
+    Ignore,
+    // Presses that miss only count when the lane has a note close by
+    NearNotes
+}
+
+impl GhostTapPolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GhostTapPolicy::Punish => "Punish",
+            GhostTapPolicy::Ignore => "Ignore",
+            GhostTapPolicy::NearNotes => "Near Notes"
+        }
+    }
+
+    /// The policy after this one in the settings toggle
+    pub fn next(&self) -> Self {
+        match self {
+            GhostTapPolicy::Punish => GhostTapPolicy::NearNotes,
+            GhostTapPolicy::NearNotes => GhostTapPolicy::Ignore,
+            GhostTapPolicy::Ignore => GhostTapPolicy::Punish
+        }
+    }
+
+    /// Whether a press on `lane` at `beat` that hit nothing counts as Incorrect,
+    /// `notes` and `special_notes` being the whole chart, judged or not
+    pub fn punishes(
+        &self,
+        beat: f32,
+        lane: f32,
+        notes: &[(f32, f32, f32)],
+        special_notes: &[SpecialNote],
+        near_range: f32,
+    ) -> bool {
+        match self {
+            GhostTapPolicy::Punish => true,
+            GhostTapPolicy::Ignore => false,
+            GhostTapPolicy::NearNotes => {
+                notes.iter().any(|x| x.1.floor() == lane && (x.0 - beat).abs() <= near_range)
+                    || special_notes.iter().any(|x| x.lanes().iter().any(|y| y.floor() == lane) && (x.beat() - beat).abs() <= near_range)
+            }
+        }
+    }
+}
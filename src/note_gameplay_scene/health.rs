@@ -0,0 +1,146 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::song::Song;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum HealthPreset {
+    Lenient,
+    #[default]
+    Normal,
+    Hard
+}
+
+impl HealthPreset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HealthPreset::Lenient => "Lenient",
+            HealthPreset::Normal => "Normal",
+            HealthPreset::Hard => "Hard"
+        }
+    }
+
+    /// The preset a chart gets from its difficulty folder when it doesn't pick one
+    pub fn for_difficulty(difficulty: &str) -> Self {
+        match difficulty {
+            "easy" | "medium" => HealthPreset::Lenient,
+            "extreme" => HealthPreset::Hard,
+            _ => HealthPreset::Normal
+        }
+    }
+
+    /// The chart's own preset, or the one for the difficulty folder it's in
+    pub fn for_song(song: &Song, song_path: &str) -> Self {
+        song.health_preset.unwrap_or_else(|| {
+            let difficulty = Path::new(song_path).parent()
+                .and_then(|x| x.file_name())
+                .and_then(|x| x.to_str())
+                .unwrap_or("");
+            Self::for_difficulty(difficulty)
+        })
+    }
+}
+
+/// Values a chart can set on top of its preset, anything left as None comes from the preset
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct HealthOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_health: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_gain: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub miss_loss: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incorrect_loss: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mine_loss: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub laser_loss: Option<i32>
+}
+
+impl HealthOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == HealthOverrides::default()
+    }
+}
+
+/// How much health a song starts with and how much each hit and mistake is worth
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HealthRules {
+    pub preset: HealthPreset,
+    // Whether the chart changed any of the preset's values
+    pub custom: bool,
+    pub max_health: i32,
+    pub hit_gain: i32,
+    pub miss_loss: i32,
+    pub incorrect_loss: i32,
+    pub mine_loss: i32,
+    pub laser_loss: i32
+}
+
+impl Default for HealthRules {
+    fn default() -> Self {
+        Self::from_preset(HealthPreset::Normal)
+    }
+}
+
+impl HealthRules {
+    pub fn from_preset(preset: HealthPreset) -> Self {
+        let normal = Self {
+            preset,
+            custom: false,
+            max_health: MAX_HEALTH,
+            hit_gain: CORRECT_HEALTH_GAIN,
+            miss_loss: HEALTH_LOSS_MISS,
+            incorrect_loss: HEALTH_LOSS_INCORRECT,
+            mine_loss: HEALTH_LOSS_MINE,
+            laser_loss: HEALTH_LOSS_LASER
+        };
+
+        match preset {
+            HealthPreset::Normal => normal,
+            // Three lasers to go down instead of two, and misses drain slower than hits refill
+            HealthPreset::Lenient => Self {
+                hit_gain: 20,
+                miss_loss: 30,
+                incorrect_loss: 25,
+                mine_loss: 60,
+                laser_loss: 150,
+                ..normal
+            },
+            HealthPreset::Hard => Self {
+                hit_gain: 10,
+                miss_loss: 75,
+                incorrect_loss: 75,
+                mine_loss: 150,
+                laser_loss: 400,
+                ..normal
+            }
+        }
+    }
+
+    pub fn with_overrides(self, overrides: &HealthOverrides) -> Self {
+        Self {
+            custom: self.custom || !overrides.is_empty(),
+            max_health: overrides.max_health.unwrap_or(self.max_health).max(1),
+            hit_gain: overrides.hit_gain.unwrap_or(self.hit_gain),
+            miss_loss: overrides.miss_loss.unwrap_or(self.miss_loss),
+            incorrect_loss: overrides.incorrect_loss.unwrap_or(self.incorrect_loss),
+            mine_loss: overrides.mine_loss.unwrap_or(self.mine_loss),
+            laser_loss: overrides.laser_loss.unwrap_or(self.laser_loss),
+            ..self
+        }
+    }
+
+    /// The song's preset with its overrides on top
+    pub fn for_song(song: &Song, song_path: &str) -> Self {
+        Self::from_preset(HealthPreset::for_song(song, song_path)).with_overrides(&song.health_overrides)
+    }
+
+    pub fn name(&self) -> String {
+        match self.custom {
+            true => format!("{} (Custom)", self.preset.name()),
+            false => self.preset.name().to_string()
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::note_gameplay_scene::constants::{GOOD_HIT_RANGE, HOLD_REGRAB_RANGE, NOTE_CORRECT_RANGE, PERFECT_HIT_RANGE, ROLL_TAP_RANGE};
+use crate::note_gameplay_scene::judgement_log::Judgement;
+
+/// How far off a hold's release can be and still count, and how long it can be let go of, in beats.
+/// Rolls count as held for `roll_tap` after each tap.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HoldWindows {
+    pub perfect: f32,
+    pub good: f32,
+    pub ok: f32,
+    pub regrab: f32,
+    pub roll_tap: f32
+}
+
+impl HoldWindows {
+    /// The windows are in beats, so they're widened with the rate to stay the same length in real time
+    pub fn new(rate: f32) -> Self {
+        Self {
+            perfect: PERFECT_HIT_RANGE * rate,
+            good: GOOD_HIT_RANGE * rate,
+            ok: NOTE_CORRECT_RANGE * rate,
+            regrab: HOLD_REGRAB_RANGE * rate,
+            roll_tap: ROLL_TAP_RANGE * rate
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HoldEvent {
+    // Beat the tick was on
+    Tick(f32),
+    // The tail was reached or let go of close enough to it
+    Finished(Judgement),
+    // Let go of for longer than the regrab window
+    Dropped
+}
+
+/// A hold note whose head has been hit
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ActiveHold {
+    // Beat, Type, Hold Length
+    pub note: (f32, f32, f32),
+    ticks_given: u32,
+    // Beat the key was let go at, None while it's held
+    pub released_at: Option<f32>
+}
+
+impl ActiveHold {
+    pub fn new(note: (f32, f32, f32)) -> Self {
+        Self {
+            note,
+            ticks_given: 0,
+            released_at: None
+        }
+    }
+
+    pub fn end_beat(&self) -> f32 {
+        self.note.0 + self.note.2
+    }
+
+    /// Ticks land every `tick_beats` after the head, up to but not on the tail
+    fn tick_beats(&self, tick_beats: f32) -> Vec<f32> {
+        let tick_count = ((self.note.2 / tick_beats).ceil() as u32).saturating_sub(1);
+        (1..=tick_count).map(|tick| self.note.0 + tick as f32 * tick_beats).collect()
+    }
+
+    /// Moves the hold up to `beat`, `held` is whether its lane key is down.
+    /// The hold is over once a Finished or Dropped event is returned.
+    pub fn update(&mut self, beat: f32, held: bool, tick_beats: f32, windows: HoldWindows) -> Vec<HoldEvent> {
+        let mut events = vec![];
+        let ticks = self.tick_beats(tick_beats);
+
+        if held {
+            self.released_at = None;
+
+            while (self.ticks_given as usize) < ticks.len() && ticks[self.ticks_given as usize] <= beat {
+                events.push(HoldEvent::Tick(ticks[self.ticks_given as usize]));
+                self.ticks_given += 1;
+            }
+
+            if beat >= self.end_beat() {
+                events.push(HoldEvent::Finished(Judgement::Perfect));
+            }
+            return events;
+        }
+
+        let released_at = *self.released_at.get_or_insert(beat);
+
+        // Ticks that pass while it's let go are lost, grabbing it again only earns the ones after
+        while (self.ticks_given as usize) < ticks.len() && ticks[self.ticks_given as usize] <= beat {
+            self.ticks_given += 1;
+        }
+        let early_by = self.end_beat() - released_at;
+
+        let judgement = if early_by <= windows.perfect {
+            Some(Judgement::Perfect)
+        } else if early_by <= windows.good {
+            Some(Judgement::Good)
+        } else if early_by <= windows.ok {
+            Some(Judgement::Ok)
+        } else {
+            None
+        };
+
+        match judgement {
+            Some(judgement) => {
+                for tick in &ticks[self.ticks_given as usize..] {
+                    events.push(HoldEvent::Tick(*tick));
+                }
+                self.ticks_given = ticks.len() as u32;
+                events.push(HoldEvent::Finished(judgement));
+            }
+            None if beat - released_at > windows.regrab => events.push(HoldEvent::Dropped),
+            None => {}
+        }
+
+        events
+    }
+
+    /// True while the hold's key is down, false while it's waiting to be grabbed again
+    pub fn is_held(&self) -> bool {
+        self.released_at.is_none()
+    }
+}
@@ -0,0 +1,261 @@
+use macroquad::color::{Color, GOLD, GRAY, GREEN, RED, SKYBLUE};
+use serde::{Deserialize, Serialize};
+use crate::audio::Sfx;
+
+// How often the health graph is sampled, in seconds of song time
+const HEALTH_SAMPLE_TIME: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Judgement {
+    Perfect,
+    Good,
+    Ok,
+    Incorrect,
+    Miss,
+    // A beat of a hold that was held down, only counts towards combo and score
+    HoldTick
+}
+
+impl Judgement {
+    /// Share of a perfect hit this judgement is worth towards accuracy
+    pub fn weight(&self) -> f32 {
+        match self {
+            Judgement::Perfect | Judgement::HoldTick => 1.0,
+            Judgement::Good => 0.8,
+            Judgement::Ok => 0.5,
+            Judgement::Incorrect | Judgement::Miss => 0.0
+        }
+    }
+
+    pub fn breaks_combo(&self) -> bool {
+        matches!(self, Judgement::Incorrect | Judgement::Miss)
+    }
+
+    /// The hitsound played when it's judged, hold ticks are too frequent to have one
+    pub fn sound(&self) -> Option<Sfx> {
+        match self {
+            Judgement::Perfect => Some(Sfx::HitPerfect),
+            Judgement::Good => Some(Sfx::HitGood),
+            Judgement::Ok => Some(Sfx::HitOk),
+            Judgement::Incorrect | Judgement::Miss => Some(Sfx::Miss),
+            Judgement::HoldTick => None
+        }
+    }
+}
+
+// Ordered worst to best so the best result can be kept with max()
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Grade {
+    F,
+    D,
+    C,
+    B,
+    A,
+    S
+}
+
+impl Grade {
+    /// Failing a song is always an F, no matter how accurate it was
+    pub fn new(accuracy: f32, cleared: bool) -> Self {
+        if !cleared {
+            Grade::F
+        } else if accuracy >= 0.95 {
+            Grade::S
+        } else if accuracy >= 0.9 {
+            Grade::A
+        } else if accuracy >= 0.8 {
+            Grade::B
+        } else if accuracy >= 0.7 {
+            Grade::C
+        } else {
+            Grade::D
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Grade::S => "S",
+            Grade::A => "A",
+            Grade::B => "B",
+            Grade::C => "C",
+            Grade::D => "D",
+            Grade::F => "F"
+        }
+    }
+}
+
+/// How well a chart has been cleared, ordered worst to best
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Serialize, Deserialize)]
+pub enum Lamp {
+    #[default]
+    NotPlayed,
+    Failed,
+    Clear,
+    // No misses or incorrect presses
+    FullCombo,
+    // Every judgement was perfect
+    AllPerfect
+}
+
+impl Lamp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lamp::NotPlayed => "Not Played",
+            Lamp::Failed => "Failed",
+            Lamp::Clear => "Clear",
+            Lamp::FullCombo => "Full Combo",
+            Lamp::AllPerfect => "All Perfect"
+        }
+    }
+
+    /// Short badge shown in the song list
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Lamp::NotPlayed => "",
+            Lamp::Failed => "F",
+            Lamp::Clear => "CL",
+            Lamp::FullCombo => "FC",
+            Lamp::AllPerfect => "AP"
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Lamp::NotPlayed => GRAY,
+            Lamp::Failed => RED,
+            Lamp::Clear => GREEN,
+            Lamp::FullCombo => SKYBLUE,
+            Lamp::AllPerfect => GOLD
+        }
+    }
+
+    pub fn is_not_played(&self) -> bool {
+        *self == Lamp::NotPlayed
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct JudgementEntry {
+    pub beat: f32,
+    pub lane: f32,
+    pub judgement: Judgement,
+    // Seconds the press was off from the note, negative is early. None when there was no timed press
+    pub offset: Option<f32>
+}
+
+/// Every judgement made while playing a song, used for the results screen
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct JudgementLog {
+    pub entries: Vec<JudgementEntry>,
+    // Seconds, Health
+    pub health_over_time: Vec<(f32, i32)>,
+    pub lasers_taken: i32,
+    // Hits in a row without a miss or incorrect press
+    pub combo: u32,
+    pub max_combo: u32,
+    // Widest a timed press can be off by, in seconds
+    pub hit_window: f32
+}
+
+impl JudgementLog {
+    pub fn new(hit_window: f32) -> Self {
+        Self {
+            hit_window,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, beat: f32, lane: f32, judgement: Judgement, offset: Option<f32>) {
+        self.entries.push(JudgementEntry { beat, lane, judgement, offset });
+
+        if judgement.breaks_combo() {
+            self.combo = 0;
+        } else {
+            self.combo += 1;
+            self.max_combo = self.max_combo.max(self.combo);
+        }
+    }
+
+    /// Call once per frame, only keeps a sample every HEALTH_SAMPLE_TIME seconds
+    pub fn record_health(&mut self, time: f32, health: i32) {
+        match self.health_over_time.last() {
+            Some((last_time, _)) if time - last_time < HEALTH_SAMPLE_TIME => {}
+            _ => self.health_over_time.push((time, health))
+        }
+    }
+
+    pub fn count(&self, judgement: Judgement) -> i32 {
+        self.entries.iter().filter(|x| x.judgement == judgement).count() as i32
+    }
+
+    /// Accuracy of every entry from `start` onwards
+    pub fn accuracy_since(&self, start: usize) -> Option<f32> {
+        accuracy(&self.entries[start.min(self.entries.len())..])
+    }
+
+    pub fn accuracy(&self) -> Option<f32> {
+        accuracy(&self.entries)
+    }
+
+    pub fn lane_accuracy(&self, lane: f32) -> Option<f32> {
+        let lane_entries: Vec<JudgementEntry> = self.entries.iter().filter(|x| x.lane == lane).copied().collect();
+        accuracy(&lane_entries)
+    }
+
+    pub fn grade(&self, cleared: bool) -> Grade {
+        Grade::new(self.accuracy().unwrap_or(0.0), cleared)
+    }
+
+    pub fn lamp(&self, cleared: bool) -> Lamp {
+        if !cleared {
+            Lamp::Failed
+        } else if self.entries.is_empty() {
+            // Nothing was judged, so there's no combo to show off
+            Lamp::Clear
+        } else if self.entries.iter().all(|x| matches!(x.judgement, Judgement::Perfect | Judgement::HoldTick)) {
+            Lamp::AllPerfect
+        } else if !self.entries.iter().any(|x| x.judgement.breaks_combo()) {
+            Lamp::FullCombo
+        } else {
+            Lamp::Clear
+        }
+    }
+
+    pub fn average_offset(&self) -> Option<f32> {
+        let offsets: Vec<f32> = self.entries.iter().filter_map(|x| x.offset).collect();
+        match offsets.is_empty() {
+            true => None,
+            false => Some(offsets.iter().sum::<f32>() / offsets.len() as f32)
+        }
+    }
+
+    /// Counts the timed presses in `bins` equal slices of the hit window, earliest first
+    pub fn offset_histogram(&self, bins: usize) -> Vec<usize> {
+        let mut histogram = vec![0; bins];
+        if self.hit_window <= 0.0 {
+            return histogram;
+        }
+
+        for offset in self.entries.iter().filter_map(|x| x.offset) {
+            let percent = (offset / self.hit_window + 1.0) / 2.0;
+            let bin = ((percent * bins as f32) as usize).min(bins - 1);
+            histogram[bin] += 1;
+        }
+
+        histogram
+    }
+}
+
+/// Share of the available points earned, None when nothing was judged.
+/// Hold ticks are left out so long holds don't outweigh the presses.
+pub fn accuracy(entries: &[JudgementEntry]) -> Option<f32> {
+    let weights: Vec<f32> = entries.iter()
+        .filter(|x| x.judgement != Judgement::HoldTick)
+        .map(|x| x.judgement.weight())
+        .collect();
+    if weights.is_empty() {
+        return None;
+    }
+
+    Some(weights.iter().sum::<f32>() / weights.len() as f32)
+}
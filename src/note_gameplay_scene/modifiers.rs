@@ -0,0 +1,167 @@
+use macroquad::miniquad;
+use macroquad::rand::RandGenerator;
+use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::attacks::Attack;
+use crate::note_gameplay_scene::song::LANES_TOP_TO_BOTTOM;
+use crate::note_gameplay_scene::special_notes::SpecialNote;
+
+pub const MIN_RATE: f32 = 0.5;
+pub const MAX_RATE: f32 = 2.0;
+pub const RATE_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LaneModifier {
+    Normal,
+    Mirror,
+    Shuffle
+}
+
+impl LaneModifier {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LaneModifier::Normal => "Normal",
+            LaneModifier::Mirror => "Mirror",
+            LaneModifier::Shuffle => "Shuffle"
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            LaneModifier::Normal => LaneModifier::Mirror,
+            LaneModifier::Mirror => LaneModifier::Shuffle,
+            LaneModifier::Shuffle => LaneModifier::Normal
+        }
+    }
+}
+
+/// Gameplay modifiers picked in the play menu
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Modifiers {
+    // Song speed, the audio and the chart both play at this rate
+    pub rate: f32,
+    pub lanes: LaneModifier,
+    // Health can't run out
+    pub no_fail: bool,
+    // Any miss, wrong press or laser hit ends the song
+    pub sudden_death: bool,
+    // Anything but a perfect hit ends the song
+    pub perfect_only: bool,
+    pub no_lasers: bool,
+    // What the lanes are shuffled with, kept with the run so restarting it shuffles them the same way
+    #[serde(default)]
+    pub shuffle_seed: u64
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            lanes: LaneModifier::Normal,
+            no_fail: false,
+            sudden_death: false,
+            perfect_only: false,
+            no_lasers: false,
+            shuffle_seed: 0
+        }
+    }
+}
+
+impl Modifiers {
+    /// The seed doesn't count, it only matters while the lanes are shuffled
+    pub fn is_default(&self) -> bool {
+        Modifiers { shuffle_seed: 0, ..*self } == Modifiers::default()
+    }
+
+    /// These modifiers with a new shuffle seed, for starting a new run
+    pub fn reseeded(&self) -> Self {
+        Self {
+            shuffle_seed: (miniquad::date::now() * 1000.0) as u64,
+            ..*self
+        }
+    }
+
+    pub fn change_rate(&mut self, amount: f32) {
+        self.rate = (((self.rate + amount) / RATE_STEP).round() * RATE_STEP).clamp(MIN_RATE, MAX_RATE);
+    }
+
+    /// What the final score is multiplied by, modifiers that make a song easier are worth less
+    pub fn score_multiplier(&self) -> f32 {
+        let mut multiplier = 1.0 + (self.rate - 1.0) * 0.5;
+
+        if self.no_fail {
+            multiplier *= 0.5;
+        }
+        if self.no_lasers {
+            multiplier *= 0.75;
+        }
+
+        multiplier
+    }
+
+    /// Short key for this set of modifiers, scores are only compared against the same tag.
+    /// Empty when no modifiers are active.
+    pub fn tag(&self) -> String {
+        let mut parts = vec![];
+
+        if self.rate != 1.0 {
+            parts.push(format!("{:.1}x", self.rate));
+        }
+        match self.lanes {
+            LaneModifier::Normal => {}
+            LaneModifier::Mirror => parts.push("MR".to_string()),
+            LaneModifier::Shuffle => parts.push("SH".to_string())
+        }
+        if self.no_fail {
+            parts.push("NF".to_string());
+        }
+        if self.sudden_death {
+            parts.push("SD".to_string());
+        }
+        if self.perfect_only {
+            parts.push("PO".to_string());
+        }
+        if self.no_lasers {
+            parts.push("NL".to_string());
+        }
+
+        parts.join(" ")
+    }
+
+    /// Rearranges the lanes of a chart's notes (Beat, Type, Hold Length), attacks and special notes
+    pub fn apply_to_chart(&self, notes: &mut [(f32, f32, f32)], attacks: &mut Vec<Attack>, special_notes: &mut [SpecialNote]) {
+        if self.no_lasers {
+            attacks.clear();
+        }
+
+        let mut remapped = LANES_TOP_TO_BOTTOM;
+        match self.lanes {
+            LaneModifier::Normal => return,
+            LaneModifier::Mirror => remapped.reverse(),
+            LaneModifier::Shuffle => {
+                // Its own generator so nothing else using rand is thrown off
+                let rng = RandGenerator::new();
+                rng.srand(self.shuffle_seed);
+                for i in (1..remapped.len()).rev() {
+                    remapped.swap(i, rng.gen_range(0, i + 1));
+                }
+            }
+        }
+
+        let remap = |lane: f32| {
+            LANES_TOP_TO_BOTTOM.iter()
+                .position(|x| *x == lane)
+                .map(|idx| remapped[idx])
+                .unwrap_or(lane)
+        };
+
+        for note in notes.iter_mut() {
+            note.1 = remap(note.1);
+        }
+        for attack in attacks.iter_mut() {
+            attack.map_lanes(remap);
+        }
+        for special_note in special_notes.iter_mut() {
+            special_note.map_lanes(remap);
+        }
+    }
+}
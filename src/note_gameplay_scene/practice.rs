@@ -0,0 +1,82 @@
+use crate::note_gameplay_scene::attacks::Attack;
+use crate::note_gameplay_scene::special_notes::SpecialNote;
+
+// Beats of the song played before the loop starts, so the first notes can be seen coming
+const LEAD_IN_BEATS: f32 = 4.0;
+// Loop points snap to this many beats
+const LOOP_SNAP: f32 = 4.0;
+
+/// A beat range that practice mode keeps replaying
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PracticeLoop {
+    pub start_beat: f32,
+    pub end_beat: f32
+}
+
+impl PracticeLoop {
+    pub fn new(start_beat: f32, end_beat: f32) -> Self {
+        Self {
+            start_beat: start_beat.max(0.0),
+            end_beat: end_beat.max(start_beat + 1.0)
+        }
+    }
+
+    pub fn whole_song(song_beats: f32) -> Self {
+        Self::new(0.0, song_beats)
+    }
+
+    /// Where playback jumps back to when the loop restarts
+    pub fn lead_in_beat(&self) -> f32 {
+        (self.start_beat - LEAD_IN_BEATS).max(0.0)
+    }
+
+    pub fn contains(&self, beat: f32) -> bool {
+        beat >= self.start_beat && beat < self.end_beat
+    }
+
+    /// Moves the start back to the measure `beat` is in
+    pub fn set_start(&mut self, beat: f32) {
+        self.start_beat = (beat / LOOP_SNAP).floor() * LOOP_SNAP;
+        if self.end_beat <= self.start_beat {
+            self.end_beat = self.start_beat + LOOP_SNAP;
+        }
+    }
+
+    /// Moves the end up to the end of the measure `beat` is in
+    pub fn set_end(&mut self, beat: f32) {
+        self.end_beat = ((beat / LOOP_SNAP).ceil() * LOOP_SNAP).max(self.start_beat + LOOP_SNAP);
+    }
+
+    /// Moves the start by `measures`, keeping it at least a measure before the end
+    pub fn move_start(&mut self, measures: f32) {
+        self.start_beat = (self.start_beat + measures * LOOP_SNAP).clamp(0.0, (self.end_beat - LOOP_SNAP).max(0.0));
+    }
+
+    /// Moves the end by `measures`, keeping it inside the song and at least a measure after the start
+    pub fn move_end(&mut self, measures: f32, song_beats: f32) {
+        self.end_beat = (self.end_beat + measures * LOOP_SNAP).min(song_beats).max(self.start_beat + LOOP_SNAP);
+    }
+
+    /// The notes (Beat, Type, Hold Length) played in the loop
+    pub fn notes_in(&self, notes: &[(f32, f32, f32)]) -> Vec<(f32, f32, f32)> {
+        notes.iter()
+            .filter(|x| self.contains(x.0))
+            .copied()
+            .collect()
+    }
+
+    /// The attacks that are firing at some point in the loop
+    pub fn attacks_in(&self, attacks: &[Attack]) -> Vec<Attack> {
+        attacks.iter()
+            .filter(|x| x.beat < self.end_beat && x.end_beat() > self.start_beat)
+            .copied()
+            .collect()
+    }
+
+    pub fn special_notes_in(&self, special_notes: &[SpecialNote]) -> Vec<SpecialNote> {
+        special_notes.iter()
+            .filter(|x| self.contains(x.beat()))
+            .cloned()
+            .collect()
+    }
+}
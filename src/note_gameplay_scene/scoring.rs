@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::judgement_log::Judgement;
+use crate::note_gameplay_scene::song::Song;
+
+/// Scoring values a chart can change, anything left as None keeps the default
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ScoringOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perfect_score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub good_score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ok_score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold_tick_score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold_tick_beats: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combo_per_multi_step: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combo_multi_step: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_combo_multi: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub laser_penalty: Option<i32>
+}
+
+impl ScoringOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == ScoringOverrides::default()
+    }
+}
+
+/// Every score comes from these rules, so a chart played the same way always scores the same.
+/// The multiplier only depends on the combo count, never on what was hit before it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScoringRules {
+    pub perfect_score: i32,
+    pub good_score: i32,
+    pub ok_score: i32,
+    // Holds are scored like a press at the head and the tail, with a tick every hold_tick_beats between
+    pub hold_tick_score: i32,
+    pub hold_tick_beats: f32,
+    pub combo_per_multi_step: u32,
+    pub combo_multi_step: f32,
+    pub max_combo_multi: f32,
+    pub laser_penalty: i32
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self {
+            perfect_score: PERFECT_HIT_SCORE,
+            good_score: GOOD_HIT_SCORE,
+            ok_score: OK_HIT_SCORE,
+            hold_tick_score: HOLD_TICK_SCORE,
+            hold_tick_beats: HOLD_TICK_BEATS,
+            combo_per_multi_step: COMBO_PER_MULTI_STEP,
+            combo_multi_step: COMBO_MULTI_STEP,
+            max_combo_multi: MAX_COMBO_MULTI,
+            laser_penalty: SCORE_LOSS_LASER
+        }
+    }
+}
+
+impl ScoringRules {
+    pub fn with_overrides(self, overrides: &ScoringOverrides) -> Self {
+        Self {
+            perfect_score: overrides.perfect_score.unwrap_or(self.perfect_score),
+            good_score: overrides.good_score.unwrap_or(self.good_score),
+            ok_score: overrides.ok_score.unwrap_or(self.ok_score),
+            hold_tick_score: overrides.hold_tick_score.unwrap_or(self.hold_tick_score),
+            // A tick every 0 beats would never end
+            hold_tick_beats: overrides.hold_tick_beats.unwrap_or(self.hold_tick_beats).max(0.05),
+            combo_per_multi_step: overrides.combo_per_multi_step.unwrap_or(self.combo_per_multi_step),
+            combo_multi_step: overrides.combo_multi_step.unwrap_or(self.combo_multi_step),
+            max_combo_multi: overrides.max_combo_multi.unwrap_or(self.max_combo_multi),
+            laser_penalty: overrides.laser_penalty.unwrap_or(self.laser_penalty)
+        }
+    }
+
+    /// The default rules with the chart's overrides on top
+    pub fn for_song(song: &Song) -> Self {
+        Self::default().with_overrides(&song.scoring_overrides)
+    }
+
+    /// Multiplier for a hit made with `combo` hits in a row before it
+    pub fn multiplier(&self, combo: u32) -> f32 {
+        let steps = combo / self.combo_per_multi_step.max(1);
+        (1.0 + steps as f32 * self.combo_multi_step).min(self.max_combo_multi)
+    }
+
+    pub fn base_score(&self, judgement: Judgement) -> i32 {
+        match judgement {
+            Judgement::Perfect => self.perfect_score,
+            Judgement::Good => self.good_score,
+            Judgement::Ok => self.ok_score,
+            Judgement::HoldTick => self.hold_tick_score,
+            Judgement::Incorrect | Judgement::Miss => 0
+        }
+    }
+
+    pub fn score(&self, judgement: Judgement, combo: u32) -> i32 {
+        (self.base_score(judgement) as f32 * self.multiplier(combo)).round() as i32
+    }
+
+    /// Beat and judgement of every head, tick and tail in a chart of notes (Beat, Type, Hold Length)
+    /// when it's played perfectly, in the order the combo reaches them
+    pub fn perfect_judgements(&self, notes: &[(f32, f32, f32)]) -> Vec<(f32, Judgement)> {
+        let mut judgements = vec![];
+        for (note_beat, _, hold_length) in notes {
+            judgements.push((*note_beat, Judgement::Perfect));
+
+            if *hold_length > 0.0 {
+                let tick_count = ((hold_length / self.hold_tick_beats).ceil() as u32).saturating_sub(1);
+                for tick in 1..=tick_count {
+                    judgements.push((note_beat + tick as f32 * self.hold_tick_beats, Judgement::HoldTick));
+                }
+                judgements.push((note_beat + hold_length, Judgement::Perfect));
+            }
+        }
+        judgements.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        judgements
+    }
+
+    /// The score for hitting every note perfectly and finishing every hold
+    pub fn max_score(&self, notes: &[(f32, f32, f32)]) -> i32 {
+        self.perfect_judgements(notes).iter().enumerate()
+            .map(|(combo, (_, judgement))| self.score(*judgement, combo as u32))
+            .sum()
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::song::{lane_position, LANES_TOP_TO_BOTTOM};
+
+/// How the player moves the ship between lanes
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum ShipControlScheme {
+    // Ship Up and Ship Down step the ship one lane at a time
+    #[default]
+    Cycle,
+    // A binding per lane sends the ship straight there
+    DirectLanes,
+    // The ship follows the mouse's height
+    Mouse,
+    // The ship moves to whichever note lane was pressed last
+    FollowLanePress
+}
+
+/// This frame's inputs that can move the ship
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ShipInput {
+    pub up_pressed: bool,
+    pub down_pressed: bool,
+    // Lane picked with the ship lane bindings
+    pub lane_pressed: Option<f32>,
+    // Last note lane pressed this frame
+    pub note_lane_pressed: Option<f32>,
+    pub mouse_y: f32
+}
+
+impl ShipControlScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShipControlScheme::Cycle => "Cycle",
+            ShipControlScheme::DirectLanes => "Direct",
+            ShipControlScheme::Mouse => "Mouse",
+            ShipControlScheme::FollowLanePress => "Follow Notes"
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ShipControlScheme::Cycle => "Ship Up and Down move one lane at a time",
+            ShipControlScheme::DirectLanes => "A key for each lane moves the ship there",
+            ShipControlScheme::Mouse => "The ship follows the mouse",
+            ShipControlScheme::FollowLanePress => "The ship moves to the last lane you hit"
+        }
+    }
+
+    /// The scheme after this one in the settings toggle
+    pub fn next(&self) -> Self {
+        match self {
+            ShipControlScheme::Cycle => ShipControlScheme::DirectLanes,
+            ShipControlScheme::DirectLanes => ShipControlScheme::Mouse,
+            ShipControlScheme::Mouse => ShipControlScheme::FollowLanePress,
+            ShipControlScheme::FollowLanePress => ShipControlScheme::Cycle
+        }
+    }
+
+    /// The height the ship heads for after this frame's input
+    pub fn wanted_height(&self, wanted_height: f32, input: &ShipInput) -> f32 {
+        match self {
+            ShipControlScheme::Cycle => {
+                let mut height = wanted_height;
+                if input.up_pressed {
+                    height = cycle_step(height, true).unwrap_or(height);
+                }
+                if input.down_pressed {
+                    height = cycle_step(height, false).unwrap_or(height);
+                }
+                height
+            }
+            ShipControlScheme::DirectLanes => input.lane_pressed.map(lane_position).unwrap_or(wanted_height),
+            ShipControlScheme::Mouse => input.mouse_y.clamp(LEFT_ARROW_POS, DOWN_ARROW_POS),
+            ShipControlScheme::FollowLanePress => input.note_lane_pressed.map(lane_position).unwrap_or(wanted_height)
+        }
+    }
+
+    /// Every height the ship can be sent to from `height`, each with the heights it passes on the way there
+    pub fn reachable(&self, height: f32) -> Vec<Vec<f32>> {
+        match self {
+            // Every press is a step, so each lane further along is reached through the ones before it
+            ShipControlScheme::Cycle => [true, false].iter()
+                .flat_map(|up| {
+                    let mut path = vec![];
+                    let mut current = height;
+                    let mut paths = vec![];
+                    while let Some(next) = cycle_step(current, *up) {
+                        path.push(next);
+                        paths.push(path.clone());
+                        current = next;
+                    }
+                    paths
+                })
+                .collect(),
+            _ => LANES_TOP_TO_BOTTOM.iter()
+                .map(|x| lane_position(*x))
+                .filter(|x| *x != height)
+                .map(|target| {
+                    LANES_TOP_TO_BOTTOM.iter()
+                        .map(|x| lane_position(*x))
+                        .filter(|x| *x != height && *x >= target.min(height) && *x <= target.max(height))
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    /// The input that moves a ship wanting to be at `wanted_height` towards `target_height`
+    pub fn input_towards(&self, wanted_height: f32, target_height: f32) -> ShipInput {
+        let target_lane = LANES_TOP_TO_BOTTOM.iter().copied().find(|x| lane_position(*x) == target_height);
+        if wanted_height == target_height {
+            return ShipInput { mouse_y: target_height, ..Default::default() };
+        }
+
+        match self {
+            ShipControlScheme::Cycle => ShipInput {
+                up_pressed: target_height < wanted_height,
+                down_pressed: target_height > wanted_height,
+                ..Default::default()
+            },
+            ShipControlScheme::DirectLanes => ShipInput { lane_pressed: target_lane, ..Default::default() },
+            ShipControlScheme::Mouse => ShipInput { mouse_y: target_height, ..Default::default() },
+            ShipControlScheme::FollowLanePress => ShipInput { note_lane_pressed: target_lane, ..Default::default() }
+        }
+    }
+}
+
+/// The lane one step up or down from `height`, the order Ship Up and Ship Down move through
+pub fn cycle_step(height: f32, up: bool) -> Option<f32> {
+    let idx = LANES_TOP_TO_BOTTOM.iter().position(|x| lane_position(*x) == height)?;
+    let next = match up {
+        true => idx.checked_sub(1)?,
+        false => idx + 1
+    };
+    LANES_TOP_TO_BOTTOM.get(next).map(|x| lane_position(*x))
+}
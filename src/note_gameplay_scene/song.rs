@@ -1,6 +1,17 @@
+use std::collections::HashMap;
+use macroquad::color::{Color, GRAY, GREEN, ORANGE, RED, SKYBLUE};
 use serde::{Deserialize, Serialize};
+use crate::note_gameplay_scene::attacks::Attack;
+use crate::note_gameplay_scene::constants::{DOWN_ARROW_POS, LEFT_ARROW_POS, RIGHT_ARROW_POS, UP_ARROW_POS};
+use crate::note_gameplay_scene::health::{HealthOverrides, HealthPreset};
+use crate::note_gameplay_scene::judgement_log::{Grade, Lamp};
+use crate::note_gameplay_scene::scoring::ScoringOverrides;
+use crate::note_gameplay_scene::special_notes::SpecialNote;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+// Note/Attack types ordered by their height on screen, top to bottom
+pub const LANES_TOP_TO_BOTTOM: [f32; 4] = [2.0, 3.0, 1.0, 4.0];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct Song {
     pub song_filepath: String,
     pub song_length: f32,
@@ -10,5 +21,68 @@ pub struct Song {
     // Beat, Type, Hold Length
     pub notes: Vec<(f32, f32, f32)>,
     // Beat, Last Time, Type
-    pub attacks: Vec<(f32, f32, f32)>
+    pub attacks: Vec<(f32, f32, f32)>,
+    // Moving lasers, projectiles, barrages and lasers with their own warm-up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub special_attacks: Vec<Attack>,
+    // Mines, chords, rolls and ship targets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub special_notes: Vec<SpecialNote>,
+    // Health rules, when there's no preset the difficulty folder picks one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_preset: Option<HealthPreset>,
+    #[serde(default, skip_serializing_if = "HealthOverrides::is_empty")]
+    pub health_overrides: HealthOverrides,
+    // Scoring values that differ from the default rules
+    #[serde(default, skip_serializing_if = "ScoringOverrides::is_empty")]
+    pub scoring_overrides: ScoringOverrides,
+    // Best scores set with modifiers, keyed by their tag
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub modded_high_scores: HashMap<String, i32>,
+    // Best grade and lamp from plays without modifiers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_grade: Option<Grade>,
+    #[serde(default, skip_serializing_if = "Lamp::is_not_played")]
+    pub lamp: Lamp
+}
+
+impl Song {
+    /// The plain lasers in `attacks` and the special attacks together
+    pub fn all_attacks(&self) -> Vec<Attack> {
+        self.attacks.iter()
+            .map(Attack::from_legacy)
+            .chain(self.special_attacks.iter().copied())
+            .collect()
+    }
+}
+
+pub fn lane_position(lane: f32) -> f32 {
+    match lane as i32 {
+        3 => UP_ARROW_POS,
+        4 => DOWN_ARROW_POS,
+        1 => RIGHT_ARROW_POS,
+        2 => LEFT_ARROW_POS,
+        _ => { panic!("Error! Note type: '{lane}' unknown") }
+    }
+}
+
+/// The color a lane's notes are drawn in
+pub fn lane_color(lane: f32) -> Color {
+    match lane as i32 {
+        3 => SKYBLUE,
+        4 => RED,
+        1 => ORANGE,
+        2 => GREEN,
+        _ => GRAY
+    }
+}
+
+pub fn lane_name(lane: f32) -> &'static str {
+    match lane as i32 {
+        3 => "Up",
+        4 => "Down",
+        1 => "Right",
+        2 => "Left",
+        _ => "Unknown"
+    }
 }
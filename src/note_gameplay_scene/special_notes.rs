@@ -0,0 +1,367 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::draw_note;
+use crate::note_gameplay_scene::holds::{ActiveHold, HoldEvent, HoldWindows};
+use crate::note_gameplay_scene::judgement_log::Judgement;
+use crate::note_gameplay_scene::song::{lane_color, lane_position};
+
+// Beats between the zig-zags drawn on a roll
+const ROLL_STRIPE_BEATS: f32 = 0.25;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum SpecialNote {
+    // Costs health if its lane is held down as it crosses the arrows
+    Mine { beat: f32, lane: f32 },
+    // Every lane has to be pressed at the same time
+    Chord {
+        beat: f32,
+        #[serde(deserialize_with = "chord_lanes")]
+        lanes: Vec<f32>
+    },
+    // Has to be tapped over and over until it ends
+    Roll { beat: f32, lane: f32, length: f32 },
+    // Scores if the ship is in its lane as it crosses the arrows
+    ShipTarget { beat: f32, lane: f32 }
+}
+
+/// A chord with no lanes would be judged without a press, so charts with one are refused
+fn chord_lanes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    let lanes = Vec::<f32>::deserialize(deserializer)?;
+    if lanes.is_empty() {
+        return Err(serde::de::Error::custom("a chord needs at least one lane"));
+    }
+
+    Ok(lanes)
+}
+
+impl SpecialNote {
+    pub fn beat(&self) -> f32 {
+        match self {
+            SpecialNote::Mine { beat, .. }
+            | SpecialNote::Chord { beat, .. }
+            | SpecialNote::Roll { beat, .. }
+            | SpecialNote::ShipTarget { beat, .. } => *beat
+        }
+    }
+
+    pub fn set_beat(&mut self, new_beat: f32) {
+        match self {
+            SpecialNote::Mine { beat, .. }
+            | SpecialNote::Chord { beat, .. }
+            | SpecialNote::Roll { beat, .. }
+            | SpecialNote::ShipTarget { beat, .. } => *beat = new_beat
+        }
+    }
+
+    pub fn lanes(&self) -> Vec<f32> {
+        match self {
+            SpecialNote::Chord { lanes, .. } => lanes.clone(),
+            SpecialNote::Mine { lane, .. }
+            | SpecialNote::Roll { lane, .. }
+            | SpecialNote::ShipTarget { lane, .. } => vec![*lane]
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpecialNote::Mine { .. } => "Mine",
+            SpecialNote::Chord { .. } => "Chord",
+            SpecialNote::Roll { .. } => "Roll",
+            SpecialNote::ShipTarget { .. } => "Ship Target"
+        }
+    }
+
+    pub fn map_lanes(&mut self, remap: impl Fn(f32) -> f32) {
+        match self {
+            SpecialNote::Chord { lanes, .. } => {
+                for lane in lanes.iter_mut() {
+                    *lane = remap(*lane);
+                }
+            }
+            SpecialNote::Mine { lane, .. }
+            | SpecialNote::Roll { lane, .. }
+            | SpecialNote::ShipTarget { lane, .. } => *lane = remap(*lane)
+        }
+    }
+}
+
+/// The notes (Beat, Type, Hold Length) that score the same as `special_notes` when they're played perfectly.
+/// Chords and ship targets are one hit, rolls score like a hold and mines don't score.
+pub fn scored_notes(special_notes: &[SpecialNote]) -> Vec<(f32, f32, f32)> {
+    special_notes.iter()
+        .filter_map(|x| match x {
+            SpecialNote::Mine { .. } => None,
+            SpecialNote::Chord { beat, lanes } => Some((*beat, lanes.first().copied().unwrap_or(1.0), 0.0)),
+            SpecialNote::Roll { beat, lane, length } => Some((*beat, *lane, *length)),
+            SpecialNote::ShipTarget { beat, lane } => Some((*beat, *lane, 0.0))
+        })
+        .collect()
+}
+
+/// Draws a special note with its head at `location`, the same as draw_note.
+/// Nothing here has its own texture, so anything that isn't an arrow is drawn with shapes.
+pub fn draw_special_note(
+    note: &SpecialNote,
+    location: f32,
+    pixels_per_beat: f32,
+    left_tex: Texture2D,
+    right_tex: Texture2D,
+    up_tex: Texture2D,
+    down_tex: Texture2D,
+) {
+    let center = location + NOTE_SIZE / 2.0;
+
+    match note {
+        SpecialNote::Mine { lane, .. } => {
+            let y = lane_position(*lane);
+            draw_circle(center, y, NOTE_SIZE * 0.4, Color::new(0.25, 0.05, 0.05, 1.0));
+            draw_circle(center, y, NOTE_SIZE * 0.2, RED);
+            draw_circle_lines(center, y, NOTE_SIZE * 0.45, 2.0, RED);
+        }
+        SpecialNote::Chord { lanes, .. } => {
+            let heights: Vec<f32> = lanes.iter().map(|x| lane_position(*x)).collect();
+            let top = heights.iter().copied().fold(f32::MAX, f32::min);
+            let bottom = heights.iter().copied().fold(f32::MIN, f32::max);
+            if top < bottom {
+                draw_line(center, top, center, bottom, 6.0, Color::new(1.0, 1.0, 1.0, 0.6));
+            }
+
+            for lane in lanes {
+                draw_note(*lane, location, left_tex, right_tex, up_tex, down_tex);
+            }
+        }
+        SpecialNote::Roll { lane, length, .. } => {
+            let y = lane_position(*lane);
+            let width = length * pixels_per_beat;
+            let color = lane_color(*lane);
+            draw_rectangle(center, y - NOTE_SIZE / 4.0, width, NOTE_SIZE / 2.0, Color::new(color.r, color.g, color.b, 0.5));
+
+            let stripe_width = ROLL_STRIPE_BEATS * pixels_per_beat;
+            let stripes = (width / stripe_width).floor() as i32;
+            for stripe in 0..stripes {
+                let x = center + stripe as f32 * stripe_width;
+                let (start, end) = match stripe % 2 == 0 {
+                    true => (y - NOTE_SIZE / 4.0, y + NOTE_SIZE / 4.0),
+                    false => (y + NOTE_SIZE / 4.0, y - NOTE_SIZE / 4.0)
+                };
+                draw_line(x, start, x + stripe_width, end, 2.0, WHITE);
+            }
+
+            draw_note(*lane, location, left_tex, right_tex, up_tex, down_tex);
+        }
+        SpecialNote::ShipTarget { lane, .. } => {
+            let y = lane_position(*lane);
+            draw_poly_lines(center, y, 4, NOTE_SIZE * 0.5, 0.0, 3.0, YELLOW);
+            draw_poly(center, y, 4, NOTE_SIZE * 0.2, 0.0, YELLOW);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpecialEvent {
+    // The note a press was closest to was at `beat`, `diff` is the note's beat minus the press's
+    Judged { beat: f32, lane: f32, judgement: Judgement, diff: Option<f32> },
+    // A beat of a roll that was kept going
+    Tick { beat: f32, lane: f32 },
+    MineHit { beat: f32, lane: f32 }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct SpecialNoteState {
+    note: SpecialNote,
+    // Lane, Beat of each chord lane pressed so far
+    chord_presses: Vec<(f32, f32)>,
+    // Set once a roll's head is hit
+    roll: Option<ActiveHold>,
+    last_tap: f32
+}
+
+/// Judges the special notes of a chart as it plays, the normal notes are judged by the scenes themselves
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SpecialNoteJudge {
+    states: Vec<SpecialNoteState>
+}
+
+impl SpecialNoteJudge {
+    pub fn new(special_notes: &[SpecialNote]) -> Self {
+        let mut states: Vec<SpecialNoteState> = special_notes.iter()
+            .map(|x| SpecialNoteState { note: x.clone(), chord_presses: vec![], roll: None, last_tap: 0.0 })
+            .collect();
+        states.sort_by(|a, b| a.note.beat().total_cmp(&b.note.beat()));
+
+        Self { states }
+    }
+
+    /// Moves the judge up to `beat`. `pressed` are the lanes pressed this frame that no normal note took,
+    /// `down` are the lanes held down and `ship_lane` is the lane the ship is in, if it's in one.
+    /// Returns what happened and the lanes whose presses were used.
+    pub fn update(
+        &mut self,
+        beat: f32,
+        pressed: &[f32],
+        down: &[f32],
+        ship_lane: Option<f32>,
+        windows: HoldWindows,
+        tick_beats: f32,
+    ) -> (Vec<SpecialEvent>, Vec<f32>) {
+        let mut events = vec![];
+        let mut used_presses: Vec<f32> = vec![];
+
+        let judge_diff = |diff: f32| {
+            if diff.abs() <= windows.perfect {
+                Judgement::Perfect
+            } else if diff.abs() <= windows.good {
+                Judgement::Good
+            } else {
+                Judgement::Ok
+            }
+        };
+
+        self.states.retain_mut(|state| {
+            match &state.note {
+                SpecialNote::Mine { beat: note_beat, lane } => {
+                    if beat < *note_beat {
+                        return true;
+                    }
+                    if down.contains(lane) {
+                        events.push(SpecialEvent::MineHit { beat: *note_beat, lane: *lane });
+                    }
+                    false
+                }
+                SpecialNote::ShipTarget { beat: note_beat, lane } => {
+                    if beat < *note_beat {
+                        return true;
+                    }
+                    let judgement = match ship_lane == Some(*lane) {
+                        true => Judgement::Perfect,
+                        false => Judgement::Miss
+                    };
+                    events.push(SpecialEvent::Judged { beat: *note_beat, lane: *lane, judgement, diff: None });
+                    false
+                }
+                SpecialNote::Chord { beat: note_beat, lanes } => {
+                    let lane = lanes.first().copied().unwrap_or(1.0);
+                    if beat > note_beat + windows.ok {
+                        events.push(SpecialEvent::Judged { beat: *note_beat, lane, judgement: Judgement::Miss, diff: None });
+                        return false;
+                    }
+                    if beat < note_beat - windows.ok {
+                        return true;
+                    }
+
+                    // Presses only count together if they're within a good hit of each other
+                    state.chord_presses.retain(|x| beat - x.1 <= windows.good);
+                    for pressed_lane in pressed {
+                        if lanes.contains(pressed_lane) && !used_presses.contains(pressed_lane) {
+                            state.chord_presses.retain(|x| x.0 != *pressed_lane);
+                            state.chord_presses.push((*pressed_lane, beat));
+                            used_presses.push(*pressed_lane);
+                        }
+                    }
+
+                    if !lanes.iter().all(|x| state.chord_presses.iter().any(|press| press.0 == *x)) {
+                        return true;
+                    }
+
+                    // The chord is only as good as its furthest off press
+                    let diff = state.chord_presses.iter()
+                        .map(|x| note_beat - x.1)
+                        .fold(0.0, |worst: f32, x| match x.abs() > worst.abs() {
+                            true => x,
+                            false => worst
+                        });
+                    events.push(SpecialEvent::Judged { beat: *note_beat, lane, judgement: judge_diff(diff), diff: Some(diff) });
+                    false
+                }
+                SpecialNote::Roll { beat: note_beat, lane, length } => {
+                    let tapped = pressed.contains(lane) && !used_presses.contains(lane);
+
+                    let Some(roll) = state.roll.as_mut() else {
+                        if beat > note_beat + windows.ok {
+                            events.push(SpecialEvent::Judged { beat: *note_beat, lane: *lane, judgement: Judgement::Miss, diff: None });
+                            return false;
+                        }
+                        if tapped && beat >= note_beat - windows.ok {
+                            let diff = note_beat - beat;
+                            used_presses.push(*lane);
+                            events.push(SpecialEvent::Judged { beat: *note_beat, lane: *lane, judgement: judge_diff(diff), diff: Some(diff) });
+                            state.last_tap = beat;
+                            state.roll = Some(ActiveHold::new((*note_beat, *lane, *length)));
+                        }
+                        return true;
+                    };
+
+                    if tapped {
+                        used_presses.push(*lane);
+                        state.last_tap = beat;
+                    }
+
+                    let mut finished = false;
+                    for event in roll.update(beat, beat - state.last_tap <= windows.roll_tap, tick_beats, windows) {
+                        match event {
+                            HoldEvent::Tick(tick_beat) => events.push(SpecialEvent::Tick { beat: tick_beat, lane: *lane }),
+                            HoldEvent::Finished(judgement) => {
+                                events.push(SpecialEvent::Judged { beat: note_beat + length, lane: *lane, judgement, diff: None });
+                                finished = true;
+                            }
+                            HoldEvent::Dropped => {
+                                events.push(SpecialEvent::Judged { beat, lane: *lane, judgement: Judgement::Miss, diff: None });
+                                finished = true;
+                            }
+                        }
+                    }
+                    !finished
+                }
+            }
+        });
+
+        (events, used_presses)
+    }
+
+    /// Draws every special note that hasn't been judged yet, rolls that are being tapped start at the arrows
+    pub fn draw(
+        &self,
+        beat: f32,
+        pixels_per_beat: f32,
+        left_tex: Texture2D,
+        right_tex: Texture2D,
+        up_tex: Texture2D,
+        down_tex: Texture2D,
+    ) {
+        for state in &self.states {
+            let mut note = state.note.clone();
+            if let (Some(roll), SpecialNote::Roll { beat: note_beat, length, .. }) = (&state.roll, &mut note) {
+                *length = (roll.end_beat() - beat).max(0.0);
+                *note_beat = beat;
+            }
+
+            let location = ((note.beat() - beat) * pixels_per_beat) + (ARROW_OFFSET - NOTE_SIZE / 2.0);
+            if location < NOTE_START_POS {
+                draw_special_note(&note, location, pixels_per_beat, left_tex, right_tex, up_tex, down_tex);
+            }
+        }
+    }
+
+    /// The lanes an autoplayer should press this frame, chords once they're reached and rolls the whole way through
+    pub fn autoplay_presses(&self, beat: f32) -> Vec<f32> {
+        let mut presses = vec![];
+        for state in &self.states {
+            match &state.note {
+                SpecialNote::Chord { beat: note_beat, lanes } if beat >= *note_beat => presses.extend(lanes),
+                SpecialNote::Roll { beat: note_beat, lane, .. } if beat >= *note_beat => presses.push(*lane),
+                _ => {}
+            }
+        }
+
+        presses
+    }
+
+    /// Lane of the next ship target coming up in the next `within` beats
+    pub fn next_ship_target(&self, beat: f32, within: f32) -> Option<f32> {
+        self.states.iter().find_map(|x| match x.note {
+            SpecialNote::ShipTarget { beat: note_beat, lane } if note_beat >= beat && note_beat - beat <= within => Some(lane),
+            _ => None
+        })
+    }
+}
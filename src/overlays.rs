@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use macroquad::prelude::*;
+use crate::game_context::GameContext;
+use crate::scene::Overlay;
+use crate::transition::Frame;
+use crate::ui::*;
+
+/// What was picked from the pause menu
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseChoice {
+    Resume,
+    Restart,
+    Quit
+}
+
+/// The gameplay pause menu, answering with what to do once it closes
+pub struct PauseMenu;
+
+/// Asks the player a yes or no question, answering true for yes
+pub struct ConfirmDialog {
+    question: String
+}
+
+impl ConfirmDialog {
+    pub fn new(question: &str) -> Self {
+        Self {
+            question: question.to_string()
+        }
+    }
+}
+
+#[async_trait]
+impl Overlay for PauseMenu {
+    type Output = PauseChoice;
+
+    async fn run(&mut self, ctx: &mut GameContext) -> PauseChoice {
+        let backdrop = ctx.take_frame();
+        let (font, nine_slice_frame, button_template) = overlay_ui(ctx);
+        // The press that opened the menu is still down on the first frame
+        let mut first_frame = true;
+
+        loop {
+            ctx.input.update();
+
+            let screen_size = ctx.window_context.active_screen_size;
+            let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
+            draw_backdrop(&backdrop, ctx);
+
+            nine_slice_frame.draw(justify_rect(screen_size.x / 2.0, screen_size.y / 2.0, 260.0, 300.0, vec2(0.5, 0.5)), WHITE);
+
+            draw_text_justified("PAUSED", vec2(screen_size.x / 2.0, screen_size.y / 2.0 - 110.0), TextParams {
+                font,
+                font_size: 100,
+                font_scale: 0.25,
+                color: WHITE,
+                ..Default::default()
+            }, vec2(0.5, 0.5));
+
+            let button_params = TextParams {
+                font,
+                font_size: 80,
+                font_scale: 0.25,
+                ..Default::default()
+            };
+            let button = |text: &str, y: f32| {
+                element_text_template(
+                    justify_rect(screen_size.x / 2.0, screen_size.y / 2.0 + y, 96.0 * 2.0, 26.0 * 2.0, vec2(0.5, 0.5)),
+                    button_template, mouse_pos, text, button_params
+                ).clicked()
+            };
+
+            let resume = button("Resume (Esc)", -45.0) || is_key_pressed(KeyCode::Escape);
+            let restart = button("Restart (R)", 20.0) || is_key_pressed(KeyCode::R);
+            let quit = button("Quit (Q)", 85.0) || is_key_pressed(KeyCode::Q);
+
+            if !first_frame {
+                if resume {
+                    return PauseChoice::Resume;
+                }
+                if restart {
+                    return PauseChoice::Restart;
+                }
+                if quit {
+                    return PauseChoice::Quit;
+                }
+            }
+            first_frame = false;
+
+            ctx.draw_window();
+
+            next_frame().await;
+        }
+    }
+}
+
+#[async_trait]
+impl Overlay for ConfirmDialog {
+    type Output = bool;
+
+    async fn run(&mut self, ctx: &mut GameContext) -> bool {
+        let backdrop = ctx.take_frame();
+        let (font, nine_slice_frame, button_template) = overlay_ui(ctx);
+        // The press that opened the dialog is still down on the first frame
+        let mut first_frame = true;
+
+        loop {
+            ctx.input.update();
+
+            let screen_size = ctx.window_context.active_screen_size;
+            let mouse_pos = ctx.window_context.camera.screen_to_world(mouse_position().into());
+            draw_backdrop(&backdrop, ctx);
+
+            nine_slice_frame.draw(justify_rect(screen_size.x / 2.0, screen_size.y / 2.0, 320.0, 140.0, vec2(0.5, 0.5)), WHITE);
+
+            draw_text_justified(&self.question, vec2(screen_size.x / 2.0, screen_size.y / 2.0 - 30.0), TextParams {
+                font,
+                font_size: 80,
+                font_scale: 0.25,
+                color: WHITE,
+                ..Default::default()
+            }, vec2(0.5, 0.5));
+
+            let button_params = TextParams {
+                font,
+                font_size: 60,
+                font_scale: 0.25,
+                ..Default::default()
+            };
+            let button = |text: &str, x: f32| {
+                element_text_template(
+                    justify_rect(screen_size.x / 2.0 + x, screen_size.y / 2.0 + 30.0, 96.0 * 1.25, 26.0 * 1.5, vec2(0.5, 0.5)),
+                    button_template, mouse_pos, text, button_params
+                ).clicked()
+            };
+
+            let yes = button("Yes (Enter)", -70.0) || is_key_pressed(KeyCode::Enter);
+            let no = button("No (Esc)", 70.0) || is_key_pressed(KeyCode::Escape);
+
+            if !first_frame {
+                if yes {
+                    return true;
+                }
+                if no {
+                    return false;
+                }
+            }
+            first_frame = false;
+
+            ctx.draw_window();
+
+            next_frame().await;
+        }
+    }
+}
+
+fn overlay_ui(ctx: &GameContext) -> (Font, Element, UITemplate) {
+    let nine_slice_frame = Element {
+        tex: ctx.assets.frame,
+        element_type: ElementType::NineSlice(vec2(10.0, 10.0))
+    };
+    let button_template = UITemplate::new(
+        Element {
+            tex: ctx.assets.button,
+            element_type: ElementType::NineSlice(vec2(10.0, 10.0))
+        },
+        Color::new(1.0, 1.0, 1.0, 1.0),
+        Some(Color::new(0.8, 0.8, 0.8, 1.0))
+    );
+
+    (ctx.assets.font, nine_slice_frame, button_template)
+}
+
+/// Draws the frame of the scene under the overlay, darkened so the overlay stands out
+fn draw_backdrop(backdrop: &Option<Frame>, ctx: &GameContext) {
+    let screen_size = ctx.window_context.active_screen_size;
+
+    set_camera(&ctx.window_context.camera);
+    clear_background(BLACK);
+    if let Some(backdrop) = backdrop {
+        backdrop.draw(0.0, WHITE, &ctx.window_context);
+    }
+    draw_rectangle(0.0, 0.0, screen_size.x, screen_size.y, Color::new(0.0, 0.0, 0.0, 0.6));
+}
@@ -1,62 +1,57 @@
 use async_trait::async_trait;
-use kira::manager::backend::cpal::CpalBackend;
-use kira::manager::{AudioManager, AudioManagerSettings};
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::*;
 
+use crate::note_gameplay_scene::attacks::{ship_hitbox, Attack};
 use crate::note_gameplay_scene::constants::*;
+use crate::note_gameplay_scene::health::HealthRules;
+use crate::note_gameplay_scene::holds::{ActiveHold, HoldEvent, HoldWindows};
+use crate::note_gameplay_scene::judgement_log::{Judgement, JudgementLog};
+use crate::note_gameplay_scene::scoring::ScoringRules;
 use crate::note_gameplay_scene::score_texts::ScoreType::Score;
 use crate::note_gameplay_scene::score_texts::{ScoreQuality, ScoreText, ScoreType};
-use crate::note_gameplay_scene::song::Song;
+use crate::note_gameplay_scene::song::{lane_position, Song, LANES_TOP_TO_BOTTOM};
+use crate::note_gameplay_scene::special_notes::{SpecialEvent, SpecialNoteJudge};
 
 use crate::game_end_scene::GameEndScene;
-use crate::main_menu_scene::MainMenuScene;
-use crate::note_gameplay_scene::{draw_hold, draw_note, ReturnTo};
+use crate::note_gameplay_scene::{draw_hold, draw_note, scroll_pixels_per_beat};
 use thousands::Separable;
-use crate::beatmap_editor_scene::BeatmapEditorScene;
-use crate::error_scene::ErrorScene;
+use crate::game_context::GameContext;
 
 use crate::ui::draw_text_justified;
 use crate::utils::*;
-use crate::Scene;
+use crate::scene::{Scene, SceneChange};
+use crate::transition::Transition;
+
+// Beats ahead the bot starts heading for a ship target
+const SHIP_TARGET_LOOKAHEAD: f32 = 2.0;
 
 pub struct PorpusScene {
-    pub window_context: WindowContext,
     pub song_path: String,
-    pub return_to: ReturnTo
+    // Goes to the results screen when the song ends instead of straight back
+    pub show_results: bool
 }
 
 impl PorpusScene {
-    pub fn new(window_context: WindowContext, song_path: &str, return_to: ReturnTo) -> Self {
+    pub fn new(song_path: &str) -> Self {
         Self {
-            window_context,
             song_path: song_path.to_string(),
-            return_to
+            show_results: true
         }
     }
 }
 
 #[async_trait]
 impl Scene for PorpusScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
         // Fonts
-        let font = match load_ttf_font("assets/fonts/pixel.ttf").await {
-            Ok(font) => font,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let font = ctx.assets.font;
 
         // Score Texts
         let mut score_texts: Vec<ScoreText> = vec![];
 
-        // Health
-        let mut health = MAX_HEALTH;
-
         // Score
         let mut score = 0;
-        let mut combo_multiplier = 1.0;
 
-        let mut perfect_notes = 0;
 
         // Color Changing
         let mut red_increasing = false;
@@ -69,54 +64,45 @@ impl Scene for PorpusScene {
         // Load the Song
         let song_json = match load_string(self.song_path.as_str()).await {
             Ok(json) => json,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Assets Missing (Verify Game Files or Reinstall)")
         };
         let song = match serde_json::from_str::<Song>(song_json.as_str()) {
             Ok(json) => json,
-            Err(_) => return Some(Box::new(ErrorScene::new("Song Format Incorrect", self.window_context.clone())))
+            Err(_) => return SceneChange::error("Song Format Incorrect")
         };
+        // Health
+        let health_rules = HealthRules::for_song(&song, &self.song_path);
+        let mut health = health_rules.max_health;
+        let scoring = ScoringRules::for_song(&song);
+
         let mut active_notes = song.notes.clone();
-        let mut song_attacks = song.attacks.clone();
+        let mut song_attacks = song.all_attacks();
 
         let mut drawn_holds = song.notes.clone();
         drawn_holds.retain(|x| x.2 != 0.0);
-        let mut active_holds: Vec<(f32, f32, f32)> = vec![];
+        let mut active_holds: Vec<ActiveHold> = vec![];
+        let hold_windows = HoldWindows::new(1.0);
+        let mut special_judge = SpecialNoteJudge::new(&song.special_notes);
         let mut hold_thickness_multi: f32 = 1.0;
         let mut thickness_multi_growing: bool = true;
 
         let beats_per_second = song.bpm / 60.0;
-        let pixels_per_beat = (NOTE_START_POS - ARROW_OFFSET) / BEATS_TO_NOTE_HIT;
-
-        let mut sound_manager =
-            AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-        let sound =
-            StaticSoundData::from_file(song.song_filepath.clone(), StaticSoundSettings::default())
-                .unwrap();
-
-        let mut music = sound_manager.play(sound).unwrap();
-
-        let config =
-            match serde_json::from_str::<Config>(&match load_string("assets/config.json").await {
-                Ok(text) => text,
-                Err(_) => return Some(Box::new(ErrorScene::new("Config File Missing", self.window_context.clone())))
-            }) {
-                Ok(config) => config,
-                Err(_) => return Some(Box::new(ErrorScene::new("Config File Error", self.window_context.clone())))
-            };
 
-        music.set_volume(config.volume, Default::default()).unwrap();
+        let mut judgements = JudgementLog::new(NOTE_CORRECT_RANGE / beats_per_second);
+
+        let mut music = match ctx.audio.play_music(&song.song_filepath) {
+            Ok(music) => music,
+            Err(err) => return SceneChange::error(&err.to_string())
+        };
+
+        let config = ctx.config.clone();
+
+        let pixels_per_beat = scroll_pixels_per_beat(&config, song.bpm);
 
         // Background
-        let background_texture =
-            match quick_load_texture("assets/images/backgrounds/Space Background (3).png").await {
-                Ok(texture) => texture,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            };
+        let background_texture = ctx.assets.gameplay_background;
 
-        let ship = match quick_load_texture("assets/images/ship.png").await  {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let ship = ctx.assets.ship;
         let mut ship_position = SHIP_FAR_RIGHT / 2.0;
         let mut ship_height = 200.0;
         let mut wanted_ship_height = RIGHT_ARROW_POS;
@@ -126,36 +112,18 @@ impl Scene for PorpusScene {
         let mut ship_alpha_growing = false;
 
         // Input Notes
-        let input_note_up = match quick_load_texture("assets/images/arrow_up.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_down = match quick_load_texture("assets/images/arrow_down.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_left = match quick_load_texture("assets/images/arrow_left.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_right = match quick_load_texture("assets/images/arrow_right.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let input_note_up = ctx.assets.arrow_up;
+        let input_note_down = ctx.assets.arrow_down;
+        let input_note_left = ctx.assets.arrow_left;
+        let input_note_right = ctx.assets.arrow_right;
 
         let mut up_scale = 1.0;
         let mut down_scale = 1.0;
         let mut left_scale = 1.0;
         let mut right_scale = 1.0;
 
-        let hold_note = match quick_load_texture("assets/images/hold.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let laser = match quick_load_texture("assets/images/laser.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let hold_note = ctx.assets.hold;
+        let laser = ctx.assets.laser;
 
         let mut game_over_timer = Timer::new(3.0, 0);
 
@@ -163,7 +131,7 @@ impl Scene for PorpusScene {
 
         loop {
             clear_background(BLACK);
-            set_camera(&self.window_context.camera);
+            set_camera(&ctx.window_context.camera);
             clear_background(DARKGRAY);
 
             draw_texture(background_texture, 0.0, 0.0, Color::new(0.5, 0.5, 0.5, 1.0));
@@ -175,7 +143,7 @@ impl Scene for PorpusScene {
             if fps_display {
                 draw_text_justified(
                     format!("{}", get_fps()).as_str(),
-                    vec2(self.window_context.active_screen_size.x - 5.0, 15.0),
+                    vec2(ctx.window_context.active_screen_size.x - 5.0, 15.0),
                     TextParams {
                         font,
                         font_size: 40,
@@ -263,18 +231,17 @@ impl Scene for PorpusScene {
                 if diff < PERFECT_HIT_RANGE / 1.5 {
                     hit_notes.push((*note_beat, *note_type, *hold_length));
 
+                    health += health_rules.hit_gain;
+                    score += scoring.score(Judgement::Perfect, judgements.combo);
+                    score_texts.push(ScoreText {
+                        timer: TEXT_LAST_TIME,
+                        score_type: Score(ScoreQuality::Perfect),
+                        y_offset: note_offset,
+                    });
+                    judgements.record(*note_beat, *note_type, Judgement::Perfect, Some(-diff / beats_per_second));
+
                     if *hold_length != 0.0 {
-                        active_holds.push((*note_beat, *note_type, *hold_length));
-                    } else {
-                        health += CORRECT_HEALTH_GAIN;
-                        score += (PERFECT_HIT_SCORE as f32 * combo_multiplier).round() as i32;
-                        combo_multiplier *= 1.05;
-                        score_texts.push(ScoreText {
-                            timer: TEXT_LAST_TIME,
-                            score_type: Score(ScoreQuality::Perfect),
-                            y_offset: note_offset,
-                        });
-                        perfect_notes += 1;
+                        active_holds.push(ActiveHold::new((*note_beat, *note_type, *hold_length)));
                     }
 
                     match *note_type as i32 {
@@ -292,10 +259,54 @@ impl Scene for PorpusScene {
                     .retain(|x| x.0 != hit_note.0 || x.1 != hit_note.1 || x.2 != hit_note.2);
             }
 
+            // Special Notes, the bot hits chords and rolls perfectly and stays off mines
+            let special_presses = special_judge.autoplay_presses(beat);
+            let ship_lane = LANES_TOP_TO_BOTTOM.iter()
+                .copied()
+                .find(|x| (lane_position(*x) - ship_height).abs() <= SHIP_TARGET_RANGE);
+
+            let (special_events, _) = special_judge.update(
+                beat,
+                &special_presses,
+                &[],
+                ship_lane,
+                hold_windows,
+                scoring.hold_tick_beats,
+            );
+            for event in special_events {
+                match event {
+                    SpecialEvent::Judged { beat: note_beat, lane, judgement, diff } => {
+                        score += scoring.score(judgement, judgements.combo);
+                        judgements.record(note_beat, lane, judgement, diff.map(|x| -x / beats_per_second));
+                        if judgement != Judgement::Miss {
+                            health += health_rules.hit_gain;
+                        }
+                        score_texts.push(ScoreText {
+                            timer: TEXT_LAST_TIME,
+                            score_type: Score(ScoreQuality::Perfect),
+                            y_offset: lane_position(lane),
+                        });
+
+                        match lane as i32 {
+                            3 => up_scale = ON_NOTE_PRESS_SCALE_FACTOR,
+                            4 => down_scale = ON_NOTE_PRESS_SCALE_FACTOR,
+                            2 => left_scale = ON_NOTE_PRESS_SCALE_FACTOR,
+                            1 => right_scale = ON_NOTE_PRESS_SCALE_FACTOR,
+                            _ => {}
+                        }
+                    }
+                    SpecialEvent::Tick { beat: tick_beat, lane } => {
+                        score += scoring.score(Judgement::HoldTick, judgements.combo);
+                        judgements.record(tick_beat, lane, Judgement::HoldTick, None);
+                    }
+                    SpecialEvent::MineHit { .. } => {}
+                }
+            }
+
             ship_height += (wanted_ship_height - ship_height) * 6.0 * get_frame_time();
 
             // Draw the SHIP (AKA: Health Bar)!
-            let health_percentage = health as f32 / MAX_HEALTH as f32;
+            let health_percentage = health as f32 / health_rules.max_health as f32;
             let wanted_ship_position = (SHIP_FAR_RIGHT * health_percentage) - 150.0;
             ship_position += (wanted_ship_position - ship_position) * get_frame_time();
 
@@ -315,40 +326,37 @@ impl Scene for PorpusScene {
                 },
             );
 
-            // Check For Hold notes failed or completed
+            // Check For Hold notes failed or completed, the bot never lets go
             let mut remove_holds = vec![];
-            for (note_beat, note_type, hold_length) in &active_holds {
-                let note_offset = match *note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => {
-                        panic!("Error! Note type: '{note_type}' unknown")
+            for active_hold in &mut active_holds {
+                let (note_beat, note_type, hold_length) = active_hold.note;
+
+                for event in active_hold.update(beat, true, scoring.hold_tick_beats, hold_windows) {
+                    match event {
+                        HoldEvent::Tick(tick_beat) => {
+                            score += scoring.score(Judgement::HoldTick, judgements.combo);
+                            judgements.record(tick_beat, note_type, Judgement::HoldTick, None);
+                        }
+                        HoldEvent::Finished(judgement) => {
+                            score += scoring.score(judgement, judgements.combo);
+                            judgements.record(note_beat + hold_length, note_type, judgement, None);
+                            remove_holds.push(active_hold.note);
+                            score_texts.push(ScoreText {
+                                timer: TEXT_LAST_TIME,
+                                score_type: Score(ScoreQuality::Perfect),
+                                y_offset: lane_position(note_type),
+                            })
+                        }
+                        HoldEvent::Dropped => remove_holds.push(active_hold.note)
                     }
-                };
-                let percent_done = ((beat - note_beat) / hold_length).clamp(0.0, 1.0);
-
-                if percent_done >= 1.0 {
-                    score += ((HOLD_SCORE_PER_BEAT as f32 / hold_length) * combo_multiplier).round()
-                        as i32;
-                    remove_holds.push((*note_beat, *note_type, *hold_length));
-                    combo_multiplier *= 1.08;
-                    score_texts.push(ScoreText {
-                        timer: TEXT_LAST_TIME,
-                        score_type: Score(ScoreQuality::Perfect),
-                        y_offset: note_offset,
-                    })
                 }
             }
 
             for remove_hold in &remove_holds {
-                active_holds.retain(|x| x != remove_hold);
+                active_holds.retain(|x| x.note != *remove_hold);
                 drawn_holds.retain(|x| x != remove_hold);
             }
 
-            combo_multiplier = combo_multiplier.clamp(1.0, MAX_COMBO_MULTI);
-
             // Draw the active Holds
             let mut remove_holds = vec![];
             for (note_beat, note_type, hold_length) in &drawn_holds {
@@ -357,7 +365,7 @@ impl Scene for PorpusScene {
                 let mut hold_width = hold_length * pixels_per_beat;
                 let hold_draw_pos = note_draw_pos + hold_width;
 
-                let is_active = active_holds.contains(&(*note_beat, *note_type, *hold_length));
+                let is_active = active_holds.iter().any(|x| x.note == (*note_beat, *note_type, *hold_length));
 
                 if (hold_draw_pos <= 15.0 && is_active) || (hold_draw_pos <= -15.0 && !is_active) {
                     remove_holds.push((*note_beat, *note_type, *hold_length))
@@ -385,7 +393,7 @@ impl Scene for PorpusScene {
 
             // Draw the active Notes
             for (note_beat, note_type, _hold_length) in &active_notes {
-                if *note_beat - beat < 15.0 {
+                if (*note_beat - beat) * pixels_per_beat < NOTE_START_POS {
                     let note_draw_pos =
                         ((note_beat - beat) * pixels_per_beat) + (ARROW_OFFSET - NOTE_SIZE / 2.0);
                     draw_note(
@@ -399,6 +407,15 @@ impl Scene for PorpusScene {
                 }
             }
 
+            special_judge.draw(
+                beat,
+                pixels_per_beat,
+                input_note_left,
+                input_note_right,
+                input_note_up,
+                input_note_down,
+            );
+
             // Scale Back Down
             if left_scale > 1.0 {
                 left_scale -= get_frame_time() * SCALE_PER_SECOND_DECREASE;
@@ -483,122 +500,59 @@ impl Scene for PorpusScene {
 
             ship_invincibility -= get_frame_time();
 
-            let mut remove_attacks = vec![];
-            for (attack_beat, last_length, note_type) in &song_attacks {
-                let note_offset = match *note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => {
-                        panic!("Error! Note type: '{note_type}' unknown")
-                    }
-                };
-
-                if *attack_beat + *last_length <= beat {
-                    remove_attacks.push((
-                        *attack_beat,
-                        *last_length,
-                        *note_type,
-                    ));
-                    continue;
-                }
-
-                if beat >= *attack_beat - 5.0 && beat <= *attack_beat {
-                    let difference = 5.0 - (*attack_beat - beat);
-
-                    draw_texture_ex(
-                        laser,
-                        0.0,
-                        note_offset - (40.0 * hold_thickness_multi) / 2.0,
-                        Color::new(red_value, green_value, blue_value, 1.0),
-                        DrawTextureParams {
-                            dest_size: Some(vec2(
-                                difference * difference * difference * 2.0,
-                                40.0 * hold_thickness_multi,
-                            )),
-                            ..Default::default()
-                        },
-                    );
-                }
-
-                if *attack_beat >= beat {
-                    continue;
-                }
+            song_attacks.retain(|x| x.end_beat() > beat);
 
-                draw_texture_ex(
-                    laser,
-                    0.0,
-                    note_offset - (40.0 * hold_thickness_multi) / 2.0,
-                    Color::new(red_value, green_value, blue_value, 1.0),
-                    DrawTextureParams {
-                        dest_size: Some(vec2(1000.0, 40.0 * hold_thickness_multi)),
-                        ..Default::default()
-                    },
-                );
+            let ship_hitbox = ship_hitbox(ship_position, ship_height);
+            for attack in &song_attacks {
+                attack.draw(beat, laser, Color::new(red_value, green_value, blue_value, 1.0), hold_thickness_multi);
 
-                if ship_height <= note_offset + 40.0
-                    && ship_height >= note_offset - 40.0
-                    && ship_invincibility <= 0.0
-                {
-                    health -= HEALTH_LOSS_LASER;
-                    score -= SCORE_LOSS_LASER;
+                if attack.hits(beat, ship_hitbox) && ship_invincibility <= 0.0 {
+                    health -= health_rules.laser_loss;
+                    score -= scoring.laser_penalty;
+                    judgements.lasers_taken += 1;
                     score_texts.push(ScoreText {
                         timer: TEXT_LAST_TIME,
                         score_type: ScoreType::Miss,
-                        y_offset: note_offset,
+                        y_offset: ship_height,
                     });
 
                     ship_invincibility = 1.0;
                 }
             }
 
+            // Head for the lane whose next attack is furthest off, out of the ones the ship can get to without crossing a laser
             let mut safest_diff = most_dangerous_note(&song_attacks, wanted_ship_height);
             let mut safest_position = wanted_ship_height;
 
-            let start_height = wanted_ship_height;
-
-            for _ in 0..3 {
-                let (moved, location) = can_move(wanted_ship_height, true);
-
-                if is_laser(&song_attacks, beat, location) {
-                    break;
+            for path in config.ship_controls.reachable(wanted_ship_height) {
+                if path.iter().any(|x| is_laser(&song_attacks, beat, *x)) {
+                    continue;
                 }
 
-                if moved {
-                    let most_dangerous = most_dangerous_note(&song_attacks, location);
-                    wanted_ship_height = location;
-                    if safest_diff < most_dangerous {
-                        safest_diff = most_dangerous;
-                        safest_position = wanted_ship_height;
-                    }
+                let location = path[path.len() - 1];
+                let most_dangerous = most_dangerous_note(&song_attacks, location);
+                if safest_diff < most_dangerous {
+                    safest_diff = most_dangerous;
+                    safest_position = location;
                 }
             }
 
-            wanted_ship_height = start_height;
-
-            for _ in 0..3 {
-                let (moved, location) = can_move(wanted_ship_height, false);
+            // Go for the next ship target when nothing in the way will fire before it's reached
+            if let Some(target_lane) = special_judge.next_ship_target(beat, SHIP_TARGET_LOOKAHEAD) {
+                let target_height = lane_position(target_lane);
+                let path_clear = LANES_TOP_TO_BOTTOM.iter()
+                    .map(|x| lane_position(*x))
+                    .filter(|x| *x >= target_height.min(ship_height) - SHIP_TARGET_RANGE && *x <= target_height.max(ship_height) + SHIP_TARGET_RANGE)
+                    .all(|x| most_dangerous_note(&song_attacks, x) > beat + SHIP_TARGET_LOOKAHEAD);
 
-                if is_laser(&song_attacks, beat, location) {
-                    break;
-                }
-
-                if moved {
-                    let most_dangerous = most_dangerous_note(&song_attacks, location);
-                    wanted_ship_height = location;
-                    if safest_diff < most_dangerous {
-                        safest_diff = most_dangerous;
-                        safest_position = wanted_ship_height;
-                    }
+                if path_clear {
+                    safest_position = target_height;
                 }
             }
 
-            wanted_ship_height = safest_position;
-
-            for remove_attack in &remove_attacks {
-                song_attacks.retain(|x| x != remove_attack);
-            }
+            // Moved with the same inputs a player would use, so the ship moves the way the chosen scheme allows
+            let ship_input = config.ship_controls.input_towards(wanted_ship_height, safest_position);
+            wanted_ship_height = config.ship_controls.wanted_height(wanted_ship_height, &ship_input);
 
             let mut remove_texts = vec![];
             for score_text in &mut score_texts {
@@ -626,7 +580,7 @@ impl Scene for PorpusScene {
 
             draw_text_justified(
                 song.credits.as_str(),
-                vec2(self.window_context.active_screen_size.x - 5.0, self.window_context.active_screen_size.y - 5.0),
+                vec2(ctx.window_context.active_screen_size.x - 5.0, ctx.window_context.active_screen_size.y - 5.0),
                 TextParams {
                     font,
                     font_size: 40,
@@ -639,7 +593,7 @@ impl Scene for PorpusScene {
 
             draw_text_justified(
                 "Performed By Porpus",
-                vec2(self.window_context.active_screen_size.x - 5.0, 5.0),
+                vec2(ctx.window_context.active_screen_size.x - 5.0, 5.0),
                 TextParams {
                     font,
                     font_size: 50,
@@ -651,25 +605,13 @@ impl Scene for PorpusScene {
             );
 
             // Clamp the health value to a max
-            health = health.clamp(0, MAX_HEALTH);
+            health = health.clamp(0, health_rules.max_health);
+
+            judgements.record_health(music.position() as f32, health);
 
             // Close Conditions
             if is_key_pressed(KeyCode::Escape) {
-                return match self.return_to.clone() {
-                    ReturnTo::MainMenu(difficulty, song_idx) => {
-                        Some(Box::new(MainMenuScene {
-                            window_context: self.window_context.clone(),
-                            selected_difficulty: Some(difficulty),
-                            selected_song_idx: Some(song_idx)
-                        }))
-                    }
-                    ReturnTo::Editor => {
-                        Some(Box::new(BeatmapEditorScene {
-                            window_context: self.window_context.clone(),
-                            song_path: self.song_path.clone()
-                        }))
-                    }
-                }
+                return SceneChange::Pop(Transition::Fade);
             }
 
             if health <= 0 {
@@ -689,8 +631,8 @@ impl Scene for PorpusScene {
                 draw_text_justified(
                     "GAME OVER",
                     vec2(
-                        self.window_context.active_screen_size.x / 2.0,
-                        (self.window_context.active_screen_size.y / 2.0)
+                        ctx.window_context.active_screen_size.x / 2.0,
+                        (ctx.window_context.active_screen_size.y / 2.0)
                             * game_over_timer.percent_done(),
                     ),
                     TextParams {
@@ -705,115 +647,55 @@ impl Scene for PorpusScene {
             }
 
             if game_over_timer.is_done() {
-                return match self.return_to.clone() {
-                    ReturnTo::MainMenu(_, _) => {
-                        Some(Box::new(GameEndScene {
-                            return_to: self.return_to.clone(),
-                            window_context: self.window_context.clone(),
-                            file_path: self.song_path.clone(),
-                            beat_level: false,
-                            score,
-                            perfect_notes,
-                            good_notes: 0,
-                            ok_notes: 0,
-                            incorrect_notes: 0,
-                            missed_notes: 0,
-                        }))}
-                    ReturnTo::Editor => { Some(Box::new(BeatmapEditorScene {
-                        window_context: self.window_context.clone(),
-                        song_path: self.song_path.clone()
-                    })) }
+                if !self.show_results {
+                    return SceneChange::Pop(Transition::Fade);
                 }
+
+                return SceneChange::Replace(Box::new(GameEndScene {
+                    file_path: self.song_path.clone(),
+                    beat_level: false,
+                    score,
+                    judgements: judgements.clone(),
+                    health_rules,
+                    modifiers: Default::default(),
+                }), Transition::Fade);
             }
 
             if music.position() >= song.song_length as f64 {
-                return match self.return_to.clone() {
-                    ReturnTo::MainMenu(_, _) => {
-                        Some(Box::new(GameEndScene {
-                            return_to: self.return_to.clone(),
-                            window_context: self.window_context.clone(),
-                            file_path: self.song_path.clone(),
-                            beat_level: false,
-                            score,
-                            perfect_notes,
-                            good_notes: 0,
-                            ok_notes: 0,
-                            incorrect_notes: 0,
-                            missed_notes: 0,
-                        }))}
-                    ReturnTo::Editor => { Some(Box::new(BeatmapEditorScene {
-                        window_context: self.window_context.clone(),
-                        song_path: self.song_path.clone()
-                    })) }
+                if !self.show_results {
+                    return SceneChange::Pop(Transition::Fade);
                 }
+
+                return SceneChange::Replace(Box::new(GameEndScene {
+                    file_path: self.song_path.clone(),
+                    beat_level: false,
+                    score,
+                    judgements: judgements.clone(),
+                    health_rules,
+                    modifiers: Default::default(),
+                }), Transition::Fade);
             }
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
 
             next_frame().await;
         }
     }
 }
 
-pub fn can_move(current_loc: f32, up: bool) -> (bool, f32) {
-    if up {
-        if current_loc == RIGHT_ARROW_POS {
-            (true, UP_ARROW_POS)
-        } else if current_loc == UP_ARROW_POS {
-            (true, LEFT_ARROW_POS)
-        } else if current_loc == DOWN_ARROW_POS {
-            (true, RIGHT_ARROW_POS)
-        } else {
-            (false, 0.0)
-        }
-    } else if current_loc == RIGHT_ARROW_POS {
-        (true, DOWN_ARROW_POS)
-    } else if current_loc == LEFT_ARROW_POS {
-        (true, UP_ARROW_POS)
-    } else if current_loc == UP_ARROW_POS {
-        (true, RIGHT_ARROW_POS)
-    } else {
-        (false, 0.0)
-    }
-}
-
-pub fn most_dangerous_note(song_attacks: &Vec<(f32, f32, f32)>, check_type: f32) -> f32 {
+/// Beat of the first attack that fires on the lane at `check_type`'s height
+pub fn most_dangerous_note(song_attacks: &[Attack], check_type: f32) -> f32 {
     let mut most_dangerous = 1000000.0;
-    for (beat, _, note_type) in song_attacks {
-        let offset = match *note_type as i32 {
-            3 => UP_ARROW_POS,
-            4 => DOWN_ARROW_POS,
-            1 => RIGHT_ARROW_POS,
-            2 => LEFT_ARROW_POS,
-            _ => {
-                panic!("Error! Note type: '{note_type}' unknown")
-            }
-        };
-
-        if offset == check_type && (*beat < most_dangerous || most_dangerous == 1000000.0) {
-            most_dangerous = *beat;
+    for attack in song_attacks {
+        let fires_on_lane = attack.lanes().iter().any(|x| lane_position(*x) == check_type);
+        if fires_on_lane && (attack.beat < most_dangerous || most_dangerous == 1000000.0) {
+            most_dangerous = attack.beat;
         }
     }
 
     most_dangerous
 }
 
-pub fn is_laser(song_attacks: &Vec<(f32, f32, f32)>, beat: f32, check_type: f32) -> bool {
-    for (other_beat, _, other_type) in song_attacks {
-        let other_offset = match *other_type as i32 {
-            3 => UP_ARROW_POS,
-            4 => DOWN_ARROW_POS,
-            1 => RIGHT_ARROW_POS,
-            2 => LEFT_ARROW_POS,
-            _ => {
-                panic!("Error! Note type: '{other_type}' unknown")
-            }
-        };
-
-        if *other_beat <= beat && other_offset == check_type {
-            return true;
-        }
-    }
-
-    false
+pub fn is_laser(song_attacks: &[Attack], beat: f32, check_type: f32) -> bool {
+    song_attacks.iter().any(|x| x.blocks(beat, check_type))
 }
@@ -1,6 +1,84 @@
 use async_trait::async_trait;
+use macroquad::window::next_frame;
+use crate::error_scene::ErrorScene;
+use crate::game_context::GameContext;
+use crate::transition::Transition;
 
 #[async_trait]
 pub trait Scene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>>;
-}
\ No newline at end of file
+    /// Runs until the scene is left. A scene that's come back to runs again,
+    /// so anything it should remember in between is kept in its fields.
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange;
+}
+
+/// A menu drawn over the last frame of the scene that opened it, which waits for its answer
+#[async_trait]
+pub trait Overlay {
+    type Output;
+
+    async fn run(&mut self, ctx: &mut GameContext) -> Self::Output;
+}
+
+/// What the scene stack does once a scene is left
+pub enum SceneChange {
+    // Opens a scene on top, coming back to this one when it's popped
+    Push(Box<dyn Scene>, Transition),
+    // Swaps this scene for another, so popping that one skips this
+    Replace(Box<dyn Scene>, Transition),
+    // Goes back to the scene underneath, quitting if there isn't one
+    Pop(Transition),
+    // Drops every open scene and starts again from this one
+    Reset(Box<dyn Scene>, Transition),
+    Quit
+}
+
+impl SceneChange {
+    /// Swaps the current scene for an error message
+    pub fn error(message: &str) -> Self {
+        SceneChange::Replace(Box::new(ErrorScene::new(message)), Transition::None)
+    }
+}
+
+/// Every open scene, the last being the one that's running
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>
+}
+
+impl SceneStack {
+    pub fn new(first: Box<dyn Scene>) -> Self {
+        Self {
+            scenes: vec![first]
+        }
+    }
+
+    /// Runs the top scene until there are none left
+    pub async fn run(mut self, ctx: &mut GameContext) {
+        while let Some(scene) = self.scenes.last_mut() {
+            let transition = match scene.run(ctx).await {
+                SceneChange::Push(scene, transition) => {
+                    self.scenes.push(scene);
+                    transition
+                }
+                SceneChange::Replace(scene, transition) => {
+                    self.scenes.pop();
+                    self.scenes.push(scene);
+                    transition
+                }
+                SceneChange::Pop(transition) => {
+                    self.scenes.pop();
+                    transition
+                }
+                SceneChange::Reset(scene, transition) => {
+                    self.scenes.clear();
+                    self.scenes.push(scene);
+                    transition
+                }
+                SceneChange::Quit => break
+            };
+
+            ctx.start_transition(transition);
+
+            next_frame().await;
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use macroquad::prelude::*;
+use macroquad_aspect::prelude::WindowContext;
+
+// Seconds a transition between scenes takes
+pub const TRANSITION_TIME: f32 = 0.35;
+// Longest frame a transition moves forward by, so a slow loading frame doesn't skip it
+const MAX_TRANSITION_STEP: f32 = 1.0 / 30.0;
+
+/// How the last frame of one scene gives way to the next scene
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transition {
+    // The next scene shows straight away
+    None,
+    // The old frame fades out over the next scene
+    Fade,
+    // The old frame slides off to the left, uncovering the next scene
+    SlideLeft,
+    // The old frame slides off to the right, uncovering the next scene
+    SlideRight
+}
+
+/// A still copy of a drawn frame, freed when it's dropped
+pub struct Frame {
+    target: RenderTarget
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32) -> Self {
+        let target = render_target(width, height);
+        target.texture.set_filter(FilterMode::Nearest);
+        Self { target }
+    }
+
+    pub fn target(&self) -> RenderTarget {
+        self.target
+    }
+
+    /// Draws the frame over the whole screen, `x` across from where it was drawn
+    pub fn draw(&self, x: f32, color: Color, window_context: &WindowContext) {
+        draw_render_target(self.target, x, color, window_context);
+    }
+}
+
+/// Draws what was rendered to `target` over the whole screen, `x` across from where it was drawn
+pub fn draw_render_target(target: RenderTarget, x: f32, color: Color, window_context: &WindowContext) {
+    // Render targets are stored upside down
+    draw_texture_ex(target.texture, x, 0.0, color, DrawTextureParams {
+        dest_size: Some(window_context.active_screen_size),
+        flip_y: true,
+        ..Default::default()
+    });
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        self.target.delete();
+    }
+}
+
+/// A transition playing over the scene that was changed to
+pub struct ActiveTransition {
+    transition: Transition,
+    // The last frame of the scene that was left
+    from: Frame,
+    time: f32
+}
+
+impl ActiveTransition {
+    pub fn new(transition: Transition, from: Frame) -> Self {
+        Self {
+            transition,
+            from,
+            time: 0.0
+        }
+    }
+
+    /// Draws the old frame over the new one, returning false once the transition is over
+    pub fn draw(&mut self, window_context: &WindowContext) -> bool {
+        self.time += get_frame_time().min(MAX_TRANSITION_STEP);
+        let progress = (self.time / TRANSITION_TIME).min(1.0);
+        // Eases out so the old frame leaves quickly and settles
+        let eased = 1.0 - (1.0 - progress).powi(3);
+        let width = window_context.active_screen_size.x;
+
+        match self.transition {
+            Transition::None => {}
+            Transition::Fade => self.from.draw(0.0, Color::new(1.0, 1.0, 1.0, 1.0 - eased), window_context),
+            Transition::SlideLeft => self.from.draw(-width * eased, WHITE, window_context),
+            Transition::SlideRight => self.from.draw(width * eased, WHITE, window_context)
+        }
+
+        progress < 1.0
+    }
+}
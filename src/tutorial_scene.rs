@@ -1,17 +1,15 @@
 use async_trait::async_trait;
-use kira::manager::{AudioManager, AudioManagerSettings};
-use kira::manager::backend::cpal::CpalBackend;
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
 use kira::tween::Tween;
 use macroquad::prelude::*;
-use macroquad_aspect::prelude::*;
-use crate::error_scene::ErrorScene;
-use crate::main_menu_scene::{Difficulty, MainMenuScene};
+use crate::game_context::GameContext;
+use crate::input::primary_binding_name;
+use crate::note_gameplay_scene::attacks::Attack;
 use crate::note_gameplay_scene::constants::*;
-use crate::note_gameplay_scene::{draw_hold, draw_note, NoteGameplayScene, ReturnTo};
-use crate::scene::Scene;
+use crate::note_gameplay_scene::ship_controls::{ShipControlScheme, ShipInput};
+use crate::note_gameplay_scene::{draw_hold, draw_note, scroll_pixels_per_beat, NoteGameplayScene};
+use crate::scene::{Scene, SceneChange};
+use crate::transition::Transition;
 use crate::ui::draw_text_justified;
-use crate::utils::{Config, quick_load_texture, u32_to_key_code};
 
 pub struct ScrollingText {
     pub text: String,
@@ -57,22 +55,17 @@ impl ScrollingText {
 }
 
 
-pub struct TutorialScene {
-    pub(crate) window_context: WindowContext
-}
+pub struct TutorialScene;
 
 #[async_trait]
 impl Scene for TutorialScene {
-    async fn run(&mut self) -> Option<Box<dyn Scene>> {
+    async fn run(&mut self, ctx: &mut GameContext) -> SceneChange {
         // Fonts
-        let font = match load_ttf_font("assets/fonts/pixel.ttf").await {
-            Ok(font) => font,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let font = ctx.assets.font;
 
         let mut scrolling_text = ScrollingText::new(
             "Hello!".to_string(), 0.05,
-            vec2(self.window_context.active_screen_size.x / 2.0, 50.0),
+            vec2(ctx.window_context.active_screen_size.x / 2.0, 50.0),
             vec2(0.5, 0.0),
             TextParams {
                 font,
@@ -85,7 +78,7 @@ impl Scene for TutorialScene {
 
         let mut scrolling_text_line_2 = ScrollingText::new(
             "".to_string(), 0.05,
-            vec2(self.window_context.active_screen_size.x / 2.0, 75.0),
+            vec2(ctx.window_context.active_screen_size.x / 2.0, 75.0),
             vec2(0.5, 0.0),
             TextParams {
                 font,
@@ -104,85 +97,61 @@ impl Scene for TutorialScene {
             (36.0, 1.0, 6.0)
         ];
         let mut song_attacks = vec![
-            (24.0, 4.0, 1.0)
+            Attack::from_legacy(&(24.0, 4.0, 1.0))
         ];
 
-        let mut sound_manager =
-            AudioManager::<CpalBackend>::new(AudioManagerSettings::default()).unwrap();
-        let sound =
-            StaticSoundData::from_file("assets/songs/music_files/Goldn.wav", StaticSoundSettings::default())
-                .unwrap();
-
-        let mut music = sound_manager.play(sound).unwrap();
-
-        let config =
-            match serde_json::from_str::<Config>(&match load_string("assets/config.json").await {
-                Ok(text) => text,
-                Err(_) => return Some(Box::new(ErrorScene::new("Config File Missing", self.window_context.clone())))
-            }) {
-                Ok(config) => config,
-                Err(_) => return Some(Box::new(ErrorScene::new("Config File Error", self.window_context.clone())))
-            };
+        let mut music = match ctx.audio.play_music("assets/songs/music_files/Goldn.wav") {
+            Ok(music) => music,
+            Err(err) => return SceneChange::error(&err.to_string())
+        };
 
-        music.set_volume(config.volume, Default::default()).unwrap();
+        let config = ctx.config.clone();
 
         // Background
-        let background_texture =
-            match quick_load_texture("assets/images/backgrounds/Space Background (3).png").await {
-                Ok(texture) => texture,
-                Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-            };
+        let background_texture = ctx.assets.gameplay_background;
 
-        let ship = match quick_load_texture("assets/images/ship.png").await  {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let ship = ctx.assets.ship;
         let ship_position = SHIP_FAR_RIGHT / 2.0;
         let mut ship_height = 200.0;
         let mut wanted_ship_height = RIGHT_ARROW_POS;
 
         // Input Notes
-        let input_note_up = match quick_load_texture("assets/images/arrow_up.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_down = match quick_load_texture("assets/images/arrow_down.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_left = match quick_load_texture("assets/images/arrow_left.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let input_note_right = match quick_load_texture("assets/images/arrow_right.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let input_note_up = ctx.assets.arrow_up;
+        let input_note_down = ctx.assets.arrow_down;
+        let input_note_left = ctx.assets.arrow_left;
+        let input_note_right = ctx.assets.arrow_right;
 
         let mut up_scale = 1.0;
         let mut down_scale = 1.0;
         let mut left_scale = 1.0;
         let mut right_scale = 1.0;
 
-        let hold_note = match quick_load_texture("assets/images/hold.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
-        let laser = match quick_load_texture("assets/images/laser.png").await {
-            Ok(texture) => texture,
-            Err(_) => return Some(Box::new(ErrorScene::new("Assets Missing (Verify Game Files or Reinstall)", self.window_context.clone())))
-        };
+        let hold_note = ctx.assets.hold;
+        let laser = ctx.assets.laser;
+
+        let left_control = &config.controls.left_arrow;
+        let right_control = &config.controls.right_arrow;
 
-        let left_control = u32_to_key_code(config.controls.left_arrow);
-        let right_control = u32_to_key_code(config.controls.right_arrow);
+        let ship_up_control = &config.controls.ship_up;
+        let ship_down_control = &config.controls.ship_down;
 
-        let ship_up_control = u32_to_key_code(config.controls.ship_up);
-        let ship_down_control = u32_to_key_code(config.controls.ship_down);
+        let lane_controls = [
+            (2.0, left_control),
+            (3.0, &config.controls.up_arrow),
+            (1.0, right_control),
+            (4.0, &config.controls.down_arrow)
+        ];
+        let ship_lane_controls = [
+            (2.0, &config.controls.ship_to_left),
+            (3.0, &config.controls.ship_to_up),
+            (1.0, &config.controls.ship_to_right),
+            (4.0, &config.controls.ship_to_down)
+        ];
 
         let mut song_progression = 0;
 
         let beats_per_second = 146.0 / 60.0;
-        let pixels_per_beat = (NOTE_START_POS - ARROW_OFFSET) / BEATS_TO_NOTE_HIT;
+        let pixels_per_beat = scroll_pixels_per_beat(&config, 146.0);
 
         let mut red_increasing = false;
         let mut red_value = 1.0;
@@ -194,9 +163,12 @@ impl Scene for TutorialScene {
         let mut thickness_multi_growing: bool = true;
         let mut hold_thickness_multi: f32 = 1.0;
 
+
         loop {
+            ctx.input.update();
+
             clear_background(BLACK);
-            set_camera(&self.window_context.camera);
+            set_camera(&ctx.window_context.camera);
             clear_background(DARKGRAY);
 
             draw_texture(background_texture, 0.0, 0.0, Color::new(0.5, 0.5, 0.5, 1.0));
@@ -260,11 +232,11 @@ impl Scene for TutorialScene {
                         music.pause(Tween::default()).unwrap();
                         song_progression += 1;
 
-                        scrolling_text.replace_text(format!("Press {:?} to hit the note!", left_control));
+                        scrolling_text.replace_text(format!("Press {} to hit the note!", primary_binding_name(left_control)));
                     }
                 }
                 1 => {
-                    if is_key_pressed(left_control) {
+                    if ctx.input.is_pressed(left_control) {
                         left_scale = ON_NOTE_PRESS_SCALE_FACTOR;
                         music.resume(Tween::default()).unwrap();
                         song_progression += 1;
@@ -277,34 +249,32 @@ impl Scene for TutorialScene {
                         music.pause(Tween::default()).unwrap();
                         song_progression += 1;
                         scrolling_text.replace_text("Look out! A laser! Move your ship".to_string());
-                        scrolling_text_line_2.replace_text(
-                            format!("out of the way with {:?} or {:?}", ship_up_control, ship_down_control)
-                        );
+                        scrolling_text_line_2.replace_text(match config.ship_controls {
+                            ShipControlScheme::Cycle => format!(
+                                "out of the way with {} or {}", primary_binding_name(ship_up_control), primary_binding_name(ship_down_control)
+                            ),
+                            ShipControlScheme::DirectLanes => format!(
+                                "out of the way with {} or {}", primary_binding_name(&config.controls.ship_to_up), primary_binding_name(&config.controls.ship_to_down)
+                            ),
+                            ShipControlScheme::Mouse => "out of the way with your mouse".to_string(),
+                            ShipControlScheme::FollowLanePress => format!(
+                                "out of the way by pressing {} or {}", primary_binding_name(&config.controls.up_arrow), primary_binding_name(&config.controls.down_arrow)
+                            )
+                        });
                     }
                 }
                 3 => {
-                    let mut done = false;
                     // Check for ship position changes
-                    if is_key_pressed(ship_up_control) {
-                        if wanted_ship_height == RIGHT_ARROW_POS {
-                            wanted_ship_height = UP_ARROW_POS;
-                        } else if wanted_ship_height == UP_ARROW_POS {
-                            wanted_ship_height = LEFT_ARROW_POS;
-                        } else if wanted_ship_height == DOWN_ARROW_POS {
-                            wanted_ship_height = RIGHT_ARROW_POS;
-                        }
-                        done = true;
-                    }
-                    if is_key_pressed(ship_down_control) {
-                        if wanted_ship_height == RIGHT_ARROW_POS {
-                            wanted_ship_height = DOWN_ARROW_POS;
-                        } else if wanted_ship_height == LEFT_ARROW_POS {
-                            wanted_ship_height = UP_ARROW_POS;
-                        } else if wanted_ship_height == UP_ARROW_POS {
-                            wanted_ship_height = RIGHT_ARROW_POS;
-                        }
-                        done = true;
-                    }
+                    let ship_input = ShipInput {
+                        up_pressed: ctx.input.is_pressed(ship_up_control),
+                        down_pressed: ctx.input.is_pressed(ship_down_control),
+                        lane_pressed: ship_lane_controls.iter().rev().find(|x| ctx.input.is_pressed(x.1)).map(|x| x.0),
+                        note_lane_pressed: lane_controls.iter().rev().find(|x| ctx.input.is_pressed(x.1)).map(|x| x.0),
+                        mouse_y: ctx.window_context.camera.screen_to_world(mouse_position().into()).y
+                    };
+                    let moved_height = config.ship_controls.wanted_height(wanted_ship_height, &ship_input);
+                    let done = moved_height != wanted_ship_height;
+                    wanted_ship_height = moved_height;
 
                     if done {
                         song_progression += 1;
@@ -317,12 +287,12 @@ impl Scene for TutorialScene {
                     if beat >= 35.9 {
                         song_progression += 1;
                         music.pause(Tween::default()).unwrap();
-                        scrolling_text.replace_text(format!("Hold {:?}", right_control));
+                        scrolling_text.replace_text(format!("Hold {}", primary_binding_name(right_control)));
                         active_notes.remove(0);
                     }
                 }
                 5 => {
-                    if is_key_down(right_control) {
+                    if ctx.input.is_down(right_control) {
                         song_progression += 1;
                         music.resume(Tween::default()).unwrap();
                     }
@@ -334,26 +304,21 @@ impl Scene for TutorialScene {
                         scrolling_text.replace_text("Congratulations! You Passed".to_string());
                         scrolling_text_line_2.replace_text("Here's Easy Goldn! Good Luck!".to_string());
                     }
-                    else if !is_key_down(right_control) {
+                    else if !ctx.input.is_down(right_control) {
                         song_progression -= 1;
                         music.pause(Tween::default()).unwrap();
-                        scrolling_text.replace_text(format!("Keep Holding {:?}!", right_control))
+                        scrolling_text.replace_text(format!("Keep Holding {}!", primary_binding_name(right_control)))
                     }
                 }
                 7 => {
                     if beat >= 60.0 {
-                        return Some(Box::new(NoteGameplayScene::new(
-                            self.window_context.clone(),
+                        return SceneChange::Replace(Box::new(NoteGameplayScene::new(
                             "assets/songs/easy/goldn.json",
-                            ReturnTo::MainMenu(Difficulty::Easy, 0))))
+                            Default::default())), Transition::Fade)
                     }
                 }
                 _ => {
-                    return Some(Box::new(MainMenuScene {
-                        window_context: self.window_context.clone(),
-                        selected_difficulty: None,
-                        selected_song_idx: None,
-                    }))
+                    return SceneChange::Pop(Transition::Fade)
                 }
             }
 
@@ -361,7 +326,7 @@ impl Scene for TutorialScene {
 
             // Draw the active Notes
             for (note_beat, note_type, _hold_length) in &active_notes {
-                if *note_beat - beat < 15.0 {
+                if (*note_beat - beat) * pixels_per_beat < NOTE_START_POS {
                     let note_draw_pos =
                         ((note_beat - beat) * pixels_per_beat) + (ARROW_OFFSET - NOTE_SIZE / 2.0);
                     draw_note(
@@ -386,63 +351,9 @@ impl Scene for TutorialScene {
                 },
             );
 
-            let mut remove_attacks = vec![];
-            for (attack_beat, last_length, note_type) in &song_attacks {
-                let note_offset = match *note_type as i32 {
-                    3 => UP_ARROW_POS,
-                    4 => DOWN_ARROW_POS,
-                    1 => RIGHT_ARROW_POS,
-                    2 => LEFT_ARROW_POS,
-                    _ => {
-                        panic!("Error! Note type: '{note_type}' unknown")
-                    }
-                };
-
-                if *attack_beat + *last_length <= beat {
-                    remove_attacks.push((
-                        *attack_beat,
-                        *last_length,
-                        *note_type,
-                    ));
-                    continue;
-                }
-
-                if beat >= *attack_beat - 5.0 && beat <= *attack_beat {
-                    let difference = 5.0 - (*attack_beat - beat);
-
-                    draw_texture_ex(
-                        laser,
-                        0.0,
-                        note_offset - (40.0 * hold_thickness_multi) / 2.0,
-                        Color::new(red_value, green_value, blue_value, 1.0),
-                        DrawTextureParams {
-                            dest_size: Some(vec2(
-                                difference * difference * difference * 2.0,
-                                40.0 * hold_thickness_multi,
-                            )),
-                            ..Default::default()
-                        },
-                    );
-                }
-
-                if *attack_beat >= beat {
-                    continue;
-                }
-
-                draw_texture_ex(
-                    laser,
-                    0.0,
-                    note_offset - (40.0 * hold_thickness_multi) / 2.0,
-                    Color::new(red_value, green_value, blue_value, 1.0),
-                    DrawTextureParams {
-                        dest_size: Some(vec2(1000.0, 40.0 * hold_thickness_multi)),
-                        ..Default::default()
-                    },
-                );
-            }
-
-            for remove_attack in &remove_attacks {
-                song_attacks.retain(|x| x != remove_attack);
+            song_attacks.retain(|x| x.end_beat() > beat);
+            for attack in &song_attacks {
+                attack.draw(beat, laser, Color::new(red_value, green_value, blue_value, 1.0), hold_thickness_multi);
             }
 
             for (note_beat, note_type, hold_length) in &holds {
@@ -544,7 +455,7 @@ impl Scene for TutorialScene {
             scrolling_text.update();
             scrolling_text_line_2.update();
 
-            draw_window(&mut self.window_context);
+            ctx.draw_window();
             next_frame().await;
         }
     }
@@ -1,10 +1,19 @@
 #![allow(dead_code)]
 
+use std::cell::RefCell;
 use macroquad::color::{Color};
 use macroquad::input::{is_mouse_button_released, MouseButton};
 use macroquad::math::{Rect, Vec2, vec2};
 use macroquad::prelude::{draw_text_ex, draw_texture_ex, DrawTextureParams, TextParams, Texture2D};
 use macroquad::text::measure_text;
+use crate::audio::Sfx;
+
+thread_local! {
+    // Buttons hovered over last frame and this frame, so a hover sound only plays when the mouse moves onto one
+    static HOVERED_BUTTONS: RefCell<(Vec<Rect>, Vec<Rect>)> = const { RefCell::new((vec![], vec![])) };
+    // Button sounds waiting for the end of the frame to be played
+    static UI_SOUNDS: RefCell<Vec<Sfx>> = const { RefCell::new(vec![]) };
+}
 
 #[derive(Clone, Copy)]
 pub struct UITemplate {
@@ -133,9 +142,40 @@ pub fn element_text_template(rect: Rect, template: UITemplate, mouse_pos: Vec2,
 
     draw_text_justified(text, center_of_rect, params, vec2(0.5, 0.5));
 
+    button_sounds(&return_value);
+
     return_value
 }
 
+/// Queues a hover sound when the mouse moves onto `button` and a click sound when it's clicked
+fn button_sounds(button: &UIElement) {
+    if button.is_hovering() {
+        let newly_hovered = HOVERED_BUTTONS.with(|hovered| {
+            let (last_frame, this_frame) = &mut *hovered.borrow_mut();
+            this_frame.push(button.rect);
+            !last_frame.contains(&button.rect)
+        });
+
+        if newly_hovered {
+            UI_SOUNDS.with(|sounds| sounds.borrow_mut().push(Sfx::UIHover));
+        }
+    }
+
+    if button.clicked() {
+        UI_SOUNDS.with(|sounds| sounds.borrow_mut().push(Sfx::UIClick));
+    }
+}
+
+/// The button sounds queued since the last call, call once at the end of each frame
+pub fn take_ui_sounds() -> Vec<Sfx> {
+    HOVERED_BUTTONS.with(|hovered| {
+        let (last_frame, this_frame) = &mut *hovered.borrow_mut();
+        *last_frame = std::mem::take(this_frame);
+    });
+
+    UI_SOUNDS.with(|sounds| std::mem::take(&mut *sounds.borrow_mut()))
+}
+
 pub fn hover_rect(rect: Rect, mouse_pos: Vec2) -> bool {
     mouse_pos.x < rect.x + rect.w && mouse_pos.x > rect.x &&
         mouse_pos.y < rect.y + rect.h && mouse_pos.y > rect.y